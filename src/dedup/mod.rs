@@ -3,37 +3,219 @@
 //! Blake3-based hashing for fast, secure deduplication of AI messages.
 
 use blake3::Hasher;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use dashmap::DashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::debug;
 
 use crate::protocol::AiMessage;
 use crate::storage::StorageLayer;
 
+/// Which granularity `SemanticDeduplicator` hashes a message at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupMode {
+    /// Hash `payload` (plus `dedup_context`) as a single unit. Two messages
+    /// dedup only if they're byte-for-byte identical.
+    WholeMessage,
+    /// Split `payload` into content-defined chunks and hash each
+    /// separately, so messages that only partially overlap still share
+    /// dedup'd regions.
+    ContentChunked,
+}
+
+/// Content-defined chunking parameters for [`DedupMode::ContentChunked`].
+///
+/// Boundaries are declared by a rolling polynomial hash over a sliding
+/// `window_size`-byte window: wherever the rolling hash's low bits are all
+/// zero under `mask`, following the "mask has `log2(avg_chunk_size)` bits
+/// set" convention from FastCDC/Rabin fingerprinting. `min_chunk_size` and
+/// `max_chunk_size` keep boundaries from clustering or running away when
+/// the input is adversarial or low-entropy.
+#[derive(Debug, Clone)]
+pub struct ChunkingConfig {
+    /// Width in bytes of the sliding window the rolling hash is taken over.
+    pub window_size: usize,
+    /// Target average chunk size in bytes; also used to derive the
+    /// boundary mask (`avg_chunk_size.next_power_of_two() - 1`).
+    pub avg_chunk_size: usize,
+    /// No boundary is declared before a chunk reaches this many bytes.
+    pub min_chunk_size: usize,
+    /// A boundary is forced if a chunk reaches this many bytes without the
+    /// rolling hash finding one on its own.
+    pub max_chunk_size: usize,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 48,
+            avg_chunk_size: 4096,
+            min_chunk_size: 1024,
+            max_chunk_size: 16384,
+        }
+    }
+}
+
+/// Rolling polynomial base used by the CDC hash. Arbitrary odd constant;
+/// only its role as a multiplier for the rolling hash matters.
+const ROLLING_BASE: u64 = 1_000_003;
+
+/// Boundary mask derived from the target average chunk size: a power of
+/// two minus one, so it has exactly `log2(avg_chunk_size)` bits set.
+fn boundary_mask(avg_chunk_size: usize) -> u64 {
+    (avg_chunk_size.max(2).next_power_of_two() as u64 - 1).max(1)
+}
+
+/// Split `payload` into content-defined chunks, returning each chunk's
+/// `(start, end)` byte range. Slides a `window_size`-byte window over the
+/// data maintaining a rolling polynomial hash, and cuts a chunk boundary
+/// wherever `hash & mask == 0`, subject to `min_chunk_size`/`max_chunk_size`.
+fn chunk_boundaries(payload: &[u8], config: &ChunkingConfig) -> Vec<(usize, usize)> {
+    let n = payload.len();
+    if n <= config.min_chunk_size {
+        return if n == 0 { Vec::new() } else { vec![(0, n)] };
+    }
+
+    let mask = boundary_mask(config.avg_chunk_size);
+    let window = config.window_size.max(1);
+
+    // BASE^(window - 1), used to remove the byte falling out of the window.
+    let mut drop_factor = 1u64;
+    for _ in 0..window.saturating_sub(1) {
+        drop_factor = drop_factor.wrapping_mul(ROLLING_BASE);
+    }
+
+    let mut boundaries = Vec::new();
+    let mut start = 0usize;
+    let mut hash: u64 = 0;
+
+    for i in 0..n {
+        hash = hash.wrapping_mul(ROLLING_BASE).wrapping_add(payload[i] as u64);
+        if i >= start + window {
+            let dropped = payload[i - window] as u64;
+            hash = hash.wrapping_sub(dropped.wrapping_mul(drop_factor).wrapping_mul(ROLLING_BASE));
+        }
+
+        let chunk_len = i + 1 - start;
+        let window_primed = chunk_len >= window;
+
+        if chunk_len >= config.max_chunk_size
+            || (window_primed && chunk_len >= config.min_chunk_size && hash & mask == 0)
+        {
+            boundaries.push((start, i + 1));
+            start = i + 1;
+            hash = 0;
+        }
+    }
+
+    if start < n {
+        boundaries.push((start, n));
+    }
+
+    boundaries
+}
+
+/// Retention class of a cached dedup entry, controlling how long it
+/// survives an epoch purge relative to the deduplicator's base `ttl_secs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionClass {
+    /// Purged once `ttl_secs` has elapsed (the usual case).
+    Default,
+    /// High-value entries: purged only after `ttl_secs * 4`, so a
+    /// frequently-hit result survives longer than one-off ones.
+    Hot,
+}
+
+impl RetentionClass {
+    fn ttl_multiplier(self) -> u64 {
+        match self {
+            RetentionClass::Default => 1,
+            RetentionClass::Hot => 4,
+        }
+    }
+}
+
+/// Point-in-time cache pressure snapshot, returned by
+/// [`SemanticDeduplicator::stats`].
+#[derive(Debug, Clone)]
+pub struct DedupStats {
+    /// Live entries in the whole-message cache
+    pub cache_entries: usize,
+    /// Live entries in the chunk store
+    pub chunk_entries: usize,
+    /// Total entries evicted so far, by capacity pressure or TTL expiry
+    pub evictions: u64,
+    /// Rough estimate of bytes held across both the cache and chunk store
+    pub estimated_bytes: usize,
+}
+
+/// Number of entries sampled per capacity-eviction pass. Evicting the
+/// oldest of a bounded random-ish sample (Redis-style approximate LRU)
+/// avoids the cost of a full scan-and-sort on every insert.
+const EVICTION_SAMPLE_SIZE: usize = 16;
+
 /// Semantic deduplicator with in-memory cache and storage backing
 pub struct SemanticDeduplicator {
-    /// In-memory cache: hash -> (timestamp_secs, result)
-    cache: DashMap<String, (i64, Vec<u8>)>,
+    /// In-memory cache: hash -> (timestamp_secs, result, retention class)
+    cache: DashMap<String, (i64, Vec<u8>, RetentionClass)>,
+    /// Chunk hash -> chunk bytes, populated only in `ContentChunked` mode.
+    chunk_store: DashMap<String, Vec<u8>>,
     /// Storage layer for persistent dedup
     storage: Option<Arc<StorageLayer>>,
     /// TTL for cached entries in seconds
     ttl_secs: u64,
+    /// Whole-message (default) vs content-defined-chunking dedup
+    mode: DedupMode,
+    /// CDC parameters, only consulted in `ContentChunked` mode
+    chunking: ChunkingConfig,
+    /// Cap on `cache` size; approximate-LRU eviction kicks in above this
+    max_entries: usize,
+    /// Total payload bytes ever passed to `check_duplicate`
+    bytes_seen: AtomicU64,
+    /// Payload bytes that were found already deduplicated (a dedup hit)
+    bytes_saved: AtomicU64,
+    /// Entries evicted so far, by capacity pressure or TTL expiry
+    evictions: AtomicU64,
 }
 
 impl SemanticDeduplicator {
     pub fn new(ttl_secs: u64) -> Self {
         Self {
             cache: DashMap::new(),
+            chunk_store: DashMap::new(),
             storage: None,
             ttl_secs,
+            mode: DedupMode::WholeMessage,
+            chunking: ChunkingConfig::default(),
+            max_entries: usize::MAX,
+            bytes_seen: AtomicU64::new(0),
+            bytes_saved: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
         }
     }
-    
+
     pub fn with_storage(mut self, storage: Arc<StorageLayer>) -> Self {
         self.storage = Some(storage);
         self
     }
-    
+
+    /// Switch this deduplicator into content-defined-chunking mode, so
+    /// `check_duplicate`/`record` operate on sub-message chunks instead of
+    /// the whole payload.
+    pub fn with_chunked_dedup(mut self, chunking: ChunkingConfig) -> Self {
+        self.mode = DedupMode::ContentChunked;
+        self.chunking = chunking;
+        self
+    }
+
+    /// Cap the whole-message cache at `max_entries`; once exceeded, inserts
+    /// trigger approximate-LRU eviction of the oldest sampled entries.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
     /// Compute semantic hash of a message
     pub fn compute_hash(&self, message: &AiMessage) -> String {
         let mut hasher = Hasher::new();
@@ -41,68 +223,211 @@ impl SemanticDeduplicator {
         hasher.update(message.dedup_context.as_bytes());
         hex::encode(hasher.finalize().as_bytes())
     }
-    
+
+    /// Split `message.payload` with content-defined chunking and return
+    /// each chunk's Blake3 hash, in order.
+    pub fn compute_chunk_hashes(&self, message: &AiMessage) -> Vec<String> {
+        chunk_boundaries(&message.payload, &self.chunking)
+            .into_iter()
+            .map(|(start, end)| hex::encode(blake3::hash(&message.payload[start..end]).as_bytes()))
+            .collect()
+    }
+
     /// Check if message is a duplicate, returns cached result if found
     pub fn check_duplicate(&self, message: &AiMessage) -> Option<Vec<u8>> {
+        self.bytes_seen.fetch_add(message.payload.len() as u64, Ordering::Relaxed);
+
+        let hit = match self.mode {
+            DedupMode::WholeMessage => self.check_duplicate_whole(message),
+            DedupMode::ContentChunked => self.check_duplicate_chunked(message),
+        };
+
+        if hit.is_some() {
+            self.bytes_saved.fetch_add(message.payload.len() as u64, Ordering::Relaxed);
+        }
+
+        hit
+    }
+
+    fn check_duplicate_whole(&self, message: &AiMessage) -> Option<Vec<u8>> {
         let hash = self.compute_hash(message);
         let now = Self::now_secs();
-        
+
         // Check in-memory cache first
         if let Some(entry) = self.cache.get(&hash) {
-            let (timestamp, result) = entry.value();
-            if now - *timestamp < self.ttl_secs as i64 {
+            let (timestamp, result, class) = entry.value();
+            if now - *timestamp < (self.ttl_secs * class.ttl_multiplier()) as i64 {
                 return Some(result.clone());
             } else {
                 drop(entry);
                 self.cache.remove(&hash);
+                self.evictions.fetch_add(1, Ordering::Relaxed);
             }
         }
-        
+
         // Check persistent storage
         if let Some(storage) = &self.storage {
             if let Some(result) = storage.check_dedup(&hash) {
                 // Populate cache
-                self.cache.insert(hash, (now, result.clone()));
+                self.cache.insert(hash, (now, result.clone(), RetentionClass::Default));
+                self.evict_if_over_capacity();
                 return Some(result);
             }
         }
-        
+
         None
     }
-    
-    /// Record a message and its result for deduplication
+
+    /// Hit only when every chunk of `message.payload` is already present in
+    /// the chunk store (in-memory or persistent); the hit reconstructs the
+    /// payload by stitching the stored chunks back together in order.
+    fn check_duplicate_chunked(&self, message: &AiMessage) -> Option<Vec<u8>> {
+        let hashes = self.compute_chunk_hashes(message);
+        if hashes.is_empty() {
+            return None;
+        }
+
+        let mut reconstructed = Vec::with_capacity(message.payload.len());
+        for hash in &hashes {
+            let chunk = self.chunk_store.get(hash).map(|c| c.value().clone())
+                .or_else(|| self.storage.as_ref().and_then(|s| s.check_dedup(hash)))?;
+            reconstructed.extend_from_slice(&chunk);
+        }
+
+        Some(reconstructed)
+    }
+
+    /// Record a message and its result for deduplication, at the default
+    /// retention class. Use [`Self::record_with_retention`] to mark a
+    /// result as high-value so it survives purges longer.
     pub fn record(&self, message: &AiMessage, result: Vec<u8>) {
+        self.record_with_retention(message, result, RetentionClass::Default);
+    }
+
+    /// Record a message and its result under the given retention class.
+    /// Has no effect on retention in `ContentChunked` mode, since the chunk
+    /// store isn't timestamped per entry.
+    pub fn record_with_retention(&self, message: &AiMessage, result: Vec<u8>, class: RetentionClass) {
+        match self.mode {
+            DedupMode::WholeMessage => self.record_whole(message, result, class),
+            DedupMode::ContentChunked => self.record_chunked(message),
+        }
+    }
+
+    fn record_whole(&self, message: &AiMessage, result: Vec<u8>, class: RetentionClass) {
         let hash = self.compute_hash(message);
         let now = Self::now_secs();
-        
+
         // Store in cache
-        self.cache.insert(hash.clone(), (now, result.clone()));
-        
+        self.cache.insert(hash.clone(), (now, result.clone(), class));
+        self.evict_if_over_capacity();
+
         // Store persistently
         if let Some(storage) = &self.storage {
             storage.write_dedup(&hash, result);
         }
     }
-    
-    /// Cleanup expired entries
+
+    /// If `cache` is over `max_entries`, evict the oldest entry among a
+    /// bounded sample until it's back under the cap (approximate LRU).
+    fn evict_if_over_capacity(&self) {
+        while self.cache.len() > self.max_entries {
+            let oldest = self.cache.iter()
+                .take(EVICTION_SAMPLE_SIZE)
+                .map(|entry| (entry.key().clone(), entry.value().0))
+                .min_by_key(|(_, timestamp)| *timestamp);
+
+            match oldest {
+                Some((key, _)) => {
+                    self.cache.remove(&key);
+                    self.evictions.fetch_add(1, Ordering::Relaxed);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Store each not-yet-seen chunk of `message.payload`, keyed by its
+    /// Blake3 hash, so future messages sharing those chunks dedup against
+    /// them.
+    fn record_chunked(&self, message: &AiMessage) {
+        for (start, end) in chunk_boundaries(&message.payload, &self.chunking) {
+            let chunk = &message.payload[start..end];
+            let hash = hex::encode(blake3::hash(chunk).as_bytes());
+
+            if self.chunk_store.contains_key(&hash) {
+                continue;
+            }
+
+            self.chunk_store.insert(hash.clone(), chunk.to_vec());
+            if let Some(storage) = &self.storage {
+                storage.write_dedup(&hash, chunk.to_vec());
+            }
+        }
+    }
+
+    /// Fraction of bytes seen by `check_duplicate` that were already
+    /// deduplicated (0.0 if nothing has been checked yet).
+    pub fn dedup_ratio(&self) -> f64 {
+        let seen = self.bytes_seen.load(Ordering::Relaxed);
+        if seen == 0 {
+            return 0.0;
+        }
+        self.bytes_saved.load(Ordering::Relaxed) as f64 / seen as f64
+    }
+
+    /// Cleanup expired entries, honoring each entry's retention class.
+    /// Equivalent to one pass of the task spawned by `start_purge_task`.
     pub fn cleanup(&self) -> usize {
         let now = Self::now_secs();
         let mut removed = 0;
-        
-        self.cache.retain(|_, (timestamp, _)| {
-            let keep = now - *timestamp < self.ttl_secs as i64;
+
+        self.cache.retain(|_, (timestamp, _, class)| {
+            let keep = now - *timestamp < (self.ttl_secs * class.ttl_multiplier()) as i64;
             if !keep { removed += 1; }
             keep
         });
-        
+
+        self.evictions.fetch_add(removed as u64, Ordering::Relaxed);
         removed
     }
-    
+
+    /// Start a background task that purges expired entries every
+    /// `interval`, so a long-running node doesn't need an operator to call
+    /// `cleanup()` by hand.
+    pub fn start_purge_task(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let dedup = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let removed = dedup.cleanup();
+                if removed > 0 {
+                    debug!(removed, "dedup cache epoch purge");
+                }
+            }
+        })
+    }
+
     /// Get cache size
     pub fn cache_size(&self) -> usize {
         self.cache.len()
     }
-    
+
+    /// Snapshot of cache pressure: entry counts, cumulative evictions, and
+    /// an estimate of bytes held across the cache and chunk store.
+    pub fn stats(&self) -> DedupStats {
+        let cache_bytes: usize = self.cache.iter().map(|e| e.value().1.len()).sum();
+        let chunk_bytes: usize = self.chunk_store.iter().map(|e| e.value().len()).sum();
+
+        DedupStats {
+            cache_entries: self.cache.len(),
+            chunk_entries: self.chunk_store.len(),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            estimated_bytes: cache_bytes + chunk_bytes,
+        }
+    }
+
     fn now_secs() -> i64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -143,4 +468,83 @@ mod tests {
         assert!(result.is_some());
         assert_eq!(result.unwrap(), b"cached response".to_vec());
     }
+
+    #[test]
+    fn test_chunk_boundaries_respect_min_and_max() {
+        let config = ChunkingConfig {
+            window_size: 16,
+            avg_chunk_size: 64,
+            min_chunk_size: 32,
+            max_chunk_size: 128,
+        };
+        let payload = vec![0xABu8; 1000];
+        let boundaries = chunk_boundaries(&payload, &config);
+
+        assert!(!boundaries.is_empty());
+        let mut covered = 0;
+        for (start, end) in &boundaries {
+            assert_eq!(*start, covered);
+            let len = end - start;
+            assert!(len <= config.max_chunk_size, "chunk {len} exceeds max");
+            covered = *end;
+        }
+        assert_eq!(covered, payload.len());
+    }
+
+    #[test]
+    fn test_content_chunked_dedup_across_similar_payloads() {
+        let dedup = SemanticDeduplicator::new(3600).with_chunked_dedup(ChunkingConfig {
+            window_size: 16,
+            avg_chunk_size: 64,
+            min_chunk_size: 32,
+            max_chunk_size: 128,
+        });
+
+        let mut payload_a = vec![0u8; 2000];
+        for (i, b) in payload_a.iter_mut().enumerate() {
+            *b = (i % 251) as u8;
+        }
+        let msg_a = AiMessage::new("agent-1".into(), payload_a.clone(), 100.0, i64::MAX);
+
+        assert!(dedup.check_duplicate(&msg_a).is_none());
+        dedup.record(&msg_a, Vec::new());
+
+        // Identical payload should now be fully reconstructed from chunks.
+        let msg_b = AiMessage::new("agent-2".into(), payload_a.clone(), 100.0, i64::MAX);
+        let hit = dedup.check_duplicate(&msg_b);
+        assert_eq!(hit, Some(payload_a));
+        assert!(dedup.dedup_ratio() > 0.0);
+    }
+
+    #[test]
+    fn test_max_entries_triggers_eviction() {
+        let dedup = SemanticDeduplicator::new(3600).with_max_entries(4);
+
+        for i in 0..10 {
+            let msg = AiMessage::new(format!("agent-{i}"), format!("payload-{i}").into_bytes(), 100.0, i64::MAX);
+            dedup.record(&msg, b"result".to_vec());
+        }
+
+        assert!(dedup.cache_size() <= 4);
+        assert!(dedup.stats().evictions > 0);
+    }
+
+    #[test]
+    fn test_hot_retention_outlives_default_on_cleanup() {
+        let dedup = SemanticDeduplicator::new(1);
+
+        let hot_msg = AiMessage::new("agent-1".into(), b"hot".to_vec(), 100.0, i64::MAX);
+        let default_msg = AiMessage::new("agent-2".into(), b"default".to_vec(), 100.0, i64::MAX);
+
+        dedup.record_with_retention(&hot_msg, b"hot-result".to_vec(), RetentionClass::Hot);
+        dedup.record(&default_msg, b"default-result".to_vec());
+
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        dedup.cleanup();
+
+        // The default-retention entry expired after ttl_secs=1, the hot one
+        // (ttl_secs * 4) has not.
+        assert!(dedup.check_duplicate(&default_msg).is_none());
+        assert!(dedup.check_duplicate(&hot_msg).is_some());
+    }
 }
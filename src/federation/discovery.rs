@@ -0,0 +1,248 @@
+//! Pluggable service-discovery backends for federation peer bootstrap.
+//!
+//! Implementations discover the current peer set from an external registry
+//! (Consul, Kubernetes, ...) so `FederationManager` doesn't require every
+//! peer to be hand-registered in orchestrated deployments.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+use super::{GeoLocation, Peer, Region};
+
+#[derive(Error, Debug)]
+pub enum DiscoveryError {
+    #[error("discovery request failed: {0}")]
+    RequestFailed(String),
+    #[error("malformed discovery response: {0}")]
+    MalformedResponse(String),
+}
+
+/// A pluggable source of peer membership, polled periodically by
+/// `FederationManager::start_discovery` to converge the peer set.
+#[async_trait]
+pub trait DiscoveryBackend: Send + Sync {
+    /// Discover the current set of peers this backend knows about.
+    async fn discover(&self) -> Result<Vec<Peer>, DiscoveryError>;
+}
+
+fn parse_tags(tags: &[String]) -> HashMap<String, String> {
+    tags.iter()
+        .filter_map(|t| t.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn region_from_labels(labels: &HashMap<String, String>) -> Option<Region> {
+    let region_id = labels.get("region-id")?.clone();
+    let region_name = labels.get("region-name").cloned().unwrap_or_else(|| region_id.clone());
+    let lat: f64 = labels.get("geo-lat").and_then(|v| v.parse().ok()).unwrap_or(0.0);
+    let lon: f64 = labels.get("geo-lon").and_then(|v| v.parse().ok()).unwrap_or(0.0);
+    let country = labels.get("geo-country").cloned().unwrap_or_default();
+    let city = labels.get("geo-city").cloned().unwrap_or_default();
+
+    Some(Region {
+        id: region_id,
+        name: region_name,
+        location: GeoLocation::new(lat, lon, &country, &city),
+    })
+}
+
+/// Discovers peers from a Consul service catalog. Expects each healthy
+/// service instance to carry a `region-id=<id>` tag (instances without one
+/// are skipped), and optionally `region-name=<name>` (defaults to the id)
+/// and `geo-lat=<f64>`/`geo-lon=<f64>`/`geo-country=<..>`/`geo-city=<..>`
+/// tags so the existing nearest-region and best-peer scoring keeps working.
+pub struct ConsulDiscoveryBackend {
+    http_client: reqwest::Client,
+    consul_url: String,
+    service_name: String,
+}
+
+impl ConsulDiscoveryBackend {
+    pub fn new(consul_url: &str, service_name: &str) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            consul_url: consul_url.trim_end_matches('/').to_string(),
+            service_name: service_name.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ConsulServiceEntry {
+    #[serde(rename = "Service")]
+    service: ConsulService,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ConsulService {
+    #[serde(rename = "ID")]
+    id: String,
+    #[serde(rename = "Address")]
+    address: String,
+    #[serde(rename = "Port")]
+    port: u16,
+    #[serde(rename = "Tags", default)]
+    tags: Vec<String>,
+}
+
+impl ConsulServiceEntry {
+    fn into_peer(self) -> Option<Peer> {
+        let labels = parse_tags(&self.service.tags);
+        let region = region_from_labels(&labels)?;
+        Some(Peer::new(
+            self.service.id,
+            format!("{}:{}", self.service.address, self.service.port),
+            region,
+        ))
+    }
+}
+
+#[async_trait]
+impl DiscoveryBackend for ConsulDiscoveryBackend {
+    async fn discover(&self) -> Result<Vec<Peer>, DiscoveryError> {
+        let url = format!(
+            "{}/v1/health/service/{}?passing=true",
+            self.consul_url, self.service_name
+        );
+        let resp = self.http_client.get(&url).send().await
+            .map_err(|e| DiscoveryError::RequestFailed(e.to_string()))?;
+        let entries: Vec<ConsulServiceEntry> = resp.json().await
+            .map_err(|e| DiscoveryError::MalformedResponse(e.to_string()))?;
+
+        Ok(entries.into_iter().filter_map(ConsulServiceEntry::into_peer).collect())
+    }
+}
+
+/// Discovers peers by watching a Kubernetes headless service's `Endpoints`
+/// object through the API server. Region/geo placement is read from
+/// annotations mirrored onto each endpoint's target-pod reference.
+pub struct KubernetesDiscoveryBackend {
+    http_client: reqwest::Client,
+    api_server_url: String,
+    namespace: String,
+    service_name: String,
+    bearer_token: Option<String>,
+}
+
+impl KubernetesDiscoveryBackend {
+    pub fn new(api_server_url: &str, namespace: &str, service_name: &str) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            api_server_url: api_server_url.trim_end_matches('/').to_string(),
+            namespace: namespace.to_string(),
+            service_name: service_name.to_string(),
+            bearer_token: None,
+        }
+    }
+
+    pub fn with_bearer_token(mut self, token: String) -> Self {
+        self.bearer_token = Some(token);
+        self
+    }
+}
+
+#[derive(Debug, serde::Deserialize, Default)]
+struct K8sEndpoints {
+    #[serde(default)]
+    subsets: Vec<K8sSubset>,
+}
+
+#[derive(Debug, serde::Deserialize, Default)]
+struct K8sSubset {
+    #[serde(default)]
+    addresses: Vec<K8sAddress>,
+    #[serde(default)]
+    ports: Vec<K8sPort>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct K8sAddress {
+    ip: String,
+    #[serde(rename = "targetRef", default)]
+    target_ref: Option<K8sTargetRef>,
+}
+
+#[derive(Debug, serde::Deserialize, Default)]
+struct K8sTargetRef {
+    #[serde(default)]
+    annotations: HashMap<String, String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct K8sPort {
+    port: u16,
+}
+
+#[async_trait]
+impl DiscoveryBackend for KubernetesDiscoveryBackend {
+    async fn discover(&self) -> Result<Vec<Peer>, DiscoveryError> {
+        let url = format!(
+            "{}/api/v1/namespaces/{}/endpoints/{}",
+            self.api_server_url, self.namespace, self.service_name
+        );
+
+        let mut request = self.http_client.get(&url);
+        if let Some(token) = &self.bearer_token {
+            request = request.bearer_auth(token);
+        }
+
+        let resp = request.send().await
+            .map_err(|e| DiscoveryError::RequestFailed(e.to_string()))?;
+        let endpoints: K8sEndpoints = resp.json().await
+            .map_err(|e| DiscoveryError::MalformedResponse(e.to_string()))?;
+
+        let mut peers = Vec::new();
+        for subset in endpoints.subsets {
+            let port = subset.ports.first().map(|p| p.port).unwrap_or(9000);
+            for addr in subset.addresses {
+                let annotations = addr.target_ref
+                    .map(|t| t.annotations)
+                    .unwrap_or_default();
+                let Some(region) = region_from_labels(&annotations) else { continue };
+                peers.push(Peer::new(addr.ip.clone(), format!("{}:{}", addr.ip, port), region));
+            }
+        }
+        Ok(peers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consul_entry_requires_region_tag() {
+        let entry = ConsulServiceEntry {
+            service: ConsulService {
+                id: "peer-1".into(),
+                address: "10.0.0.1".into(),
+                port: 9000,
+                tags: vec!["env=prod".into()],
+            },
+        };
+        assert!(entry.into_peer().is_none());
+    }
+
+    #[test]
+    fn test_consul_entry_parses_region_and_geo_tags() {
+        let entry = ConsulServiceEntry {
+            service: ConsulService {
+                id: "peer-1".into(),
+                address: "10.0.0.1".into(),
+                port: 9000,
+                tags: vec![
+                    "region-id=us-west-1".into(),
+                    "geo-lat=37.0".into(),
+                    "geo-lon=-122.0".into(),
+                ],
+            },
+        };
+        let peer = entry.into_peer().unwrap();
+        assert_eq!(peer.address, "10.0.0.1:9000");
+        assert_eq!(peer.region.id, "us-west-1");
+        assert_eq!(peer.region.location.latitude, 37.0);
+    }
+}
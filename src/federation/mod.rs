@@ -4,6 +4,7 @@
 //! and cross-cluster coordination.
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use dashmap::DashMap;
@@ -11,6 +12,12 @@ use parking_lot::RwLock;
 use thiserror::Error;
 use tracing::{debug, info, warn, error};
 
+mod discovery;
+pub use discovery::{ConsulDiscoveryBackend, DiscoveryBackend, DiscoveryError, KubernetesDiscoveryBackend};
+
+mod heartbeat;
+pub use heartbeat::{PeerProber, PhiAccrualDetector};
+
 #[derive(Error, Debug)]
 pub enum FederationError {
     #[error("Peer not found: {0}")]
@@ -23,6 +30,23 @@ pub enum FederationError {
     ForwardingFailed(String),
     #[error("Cluster unhealthy: {0}")]
     ClusterUnhealthy(String),
+    #[error("Invalid region role transition for {region_id}: {from:?} -> {to:?}")]
+    InvalidRoleTransition {
+        region_id: String,
+        from: RegionRoleState,
+        to: RegionRoleState,
+    },
+}
+
+/// Leadership role of a region within the federation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionRoleState {
+    /// Actively accepting and serving new work
+    Leader,
+    /// Replicating from a leader, not itself admitting new work
+    Follower,
+    /// Mid-failover: draining in-flight work, rejecting anything new
+    Downgrading,
 }
 
 /// Region identifier
@@ -106,8 +130,7 @@ impl Peer {
     }
     
     pub fn is_healthy(&self) -> bool {
-        matches!(self.status, PeerStatus::Healthy) &&
-        self.last_heartbeat.elapsed() < Duration::from_secs(30)
+        matches!(self.status, PeerStatus::Healthy)
     }
     
     pub fn load_percentage(&self) -> f64 {
@@ -145,6 +168,10 @@ pub struct FederationConfig {
     pub prefer_local: bool,
     /// Max hops for message forwarding
     pub max_forward_hops: u8,
+    /// Phi value above which a peer is marked `Degraded`
+    pub phi_suspect_threshold: f64,
+    /// Phi value above which a peer is considered dead and removed
+    pub phi_dead_threshold: f64,
 }
 
 impl Default for FederationConfig {
@@ -161,6 +188,8 @@ impl Default for FederationConfig {
             cross_region_routing: true,
             prefer_local: true,
             max_forward_hops: 3,
+            phi_suspect_threshold: 5.0,
+            phi_dead_threshold: 12.0,
         }
     }
 }
@@ -174,8 +203,23 @@ pub struct FederationManager {
     peers_by_region: DashMap<String, Vec<String>>,
     /// Region metadata
     regions: DashMap<String, Region>,
-    /// Routing table: destination -> next hop peer
+    /// Routing table: destination region -> next hop peer id
     routing_table: Arc<RwLock<HashMap<String, String>>>,
+    /// Full region path (local excluded) computed by the last link-state
+    /// recomputation, keyed by destination region.
+    region_paths: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    /// Gossip version of each known peer record (last-writer-wins CRDT)
+    peer_versions: DashMap<String, u64>,
+    /// Id of the cluster that authored the currently-applied version of each
+    /// peer record, used to break ties between two records sharing a version
+    /// number (see `merge_gossip`)
+    peer_record_origin: DashMap<String, String>,
+    /// Monotonically increasing counter used to version local writes
+    version_counter: AtomicU64,
+    /// Leadership role per region, used to gate writes during failover
+    region_roles: DashMap<String, RegionRoleState>,
+    /// Phi-accrual failure detector state per peer
+    failure_detectors: DashMap<String, PhiAccrualDetector>,
 }
 
 impl FederationManager {
@@ -188,33 +232,267 @@ impl FederationManager {
             peers_by_region: DashMap::new(),
             regions: DashMap::new(),
             routing_table: Arc::new(RwLock::new(HashMap::new())),
+            region_paths: Arc::new(RwLock::new(HashMap::new())),
+            peer_versions: DashMap::new(),
+            peer_record_origin: DashMap::new(),
+            version_counter: AtomicU64::new(0),
+            region_roles: DashMap::new(),
+            failure_detectors: DashMap::new(),
         };
-        
+
         manager.regions.insert(local_region.id.clone(), local_region);
         manager
     }
-    
+
     /// Register a peer node
     pub fn register_peer(&self, peer: Peer) {
+        let version = self.next_version();
+        let peer_id = peer.id.clone();
+        self.upsert_peer(peer);
+        self.peer_versions.insert(peer_id.clone(), version);
+        self.peer_record_origin.insert(peer_id, self.config.cluster_id.clone());
+        self.recompute_routes();
+    }
+
+    /// Insert or replace a peer's local bookkeeping (region index, regions map)
+    /// without touching its gossip version.
+    fn upsert_peer(&self, peer: Peer) {
         let region_id = peer.region.id.clone();
         let peer_id = peer.id.clone();
-        
+
         // Add region if new
         if !self.regions.contains_key(&region_id) {
             self.regions.insert(region_id.clone(), peer.region.clone());
         }
-        
+
         // Add to peers
         self.peers.insert(peer_id.clone(), peer);
-        
+
         // Add to region index
-        self.peers_by_region
+        let mut region_peers = self.peers_by_region
             .entry(region_id.clone())
-            .or_insert_with(Vec::new)
-            .push(peer_id.clone());
-        
+            .or_insert_with(Vec::new);
+        if !region_peers.contains(&peer_id) {
+            region_peers.push(peer_id.clone());
+        }
+        drop(region_peers);
+
         info!(peer_id = %peer_id, region = %region_id, "Registered peer");
     }
+
+    fn next_version(&self) -> u64 {
+        self.version_counter.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Estimated edge weight (ms) from `from` to `to` for link-state routing.
+    /// From the local region we prefer a healthy peer's measured `latency_ms`;
+    /// otherwise (and for any other region pair, where we have no direct
+    /// measurement) we fall back to a distance-derived estimate so the graph
+    /// stays fully connected.
+    fn edge_weight(&self, from: &Region, to: &Region) -> f64 {
+        if from.id == self.config.region.id {
+            if let Some(peer) = self.get_best_peer(&to.id) {
+                return peer.latency_ms as f64;
+            }
+        }
+        // ~100 km/ms is a reasonable stand-in for unmeasured links (roughly
+        // speed-of-light in fiber plus routing overhead).
+        from.location.distance_to(&to.location) / 100.0
+    }
+
+    /// Recompute shortest paths from the local region to every other known
+    /// region (Dijkstra over a fully-connected region graph) and refresh
+    /// `routing_table` (next hop peer id per destination) and `region_paths`
+    /// (full ordered region chain per destination). Unhealthy peers are
+    /// skipped when picking the next hop for a given region.
+    fn recompute_routes(&self) {
+        let local_id = self.config.region.id.clone();
+        let regions: Vec<Region> = self.regions.iter().map(|r| r.clone()).collect();
+
+        let mut dist: HashMap<String, f64> = HashMap::new();
+        let mut prev: HashMap<String, String> = HashMap::new();
+        let mut unvisited: std::collections::HashSet<String> =
+            regions.iter().map(|r| r.id.clone()).collect();
+
+        for region in &regions {
+            dist.insert(region.id.clone(), if region.id == local_id { 0.0 } else { f64::INFINITY });
+        }
+
+        while !unvisited.is_empty() {
+            let current_id = unvisited.iter()
+                .min_by(|a, b| {
+                    dist.get(*a).copied().unwrap_or(f64::INFINITY)
+                        .partial_cmp(&dist.get(*b).copied().unwrap_or(f64::INFINITY))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .cloned();
+            let Some(current_id) = current_id else { break };
+            unvisited.remove(&current_id);
+
+            let current_dist = dist.get(&current_id).copied().unwrap_or(f64::INFINITY);
+            if current_dist.is_infinite() {
+                continue;
+            }
+            let Some(current_region) = self.regions.get(&current_id).map(|r| r.clone()) else { continue };
+
+            for region in &regions {
+                if region.id == current_id || !unvisited.contains(&region.id) {
+                    continue;
+                }
+                let weight = self.edge_weight(&current_region, region);
+                let candidate = current_dist + weight;
+                if candidate < dist.get(&region.id).copied().unwrap_or(f64::INFINITY) {
+                    dist.insert(region.id.clone(), candidate);
+                    prev.insert(region.id.clone(), current_id.clone());
+                }
+            }
+        }
+
+        let mut routing_table = HashMap::new();
+        let mut region_paths = HashMap::new();
+
+        for region in &regions {
+            if region.id == local_id {
+                continue;
+            }
+            if !dist.get(&region.id).copied().unwrap_or(f64::INFINITY).is_finite() {
+                continue;
+            }
+
+            // Walk the `prev` chain back to the local region to recover the
+            // full hop path, then reverse it.
+            let mut path = vec![region.id.clone()];
+            let mut cursor = region.id.clone();
+            while let Some(p) = prev.get(&cursor) {
+                if *p == local_id {
+                    break;
+                }
+                path.push(p.clone());
+                cursor = p.clone();
+            }
+            path.reverse();
+
+            if let Some(first_hop_region) = path.first() {
+                if let Some(peer) = self.get_best_peer(first_hop_region) {
+                    routing_table.insert(region.id.clone(), peer.id.clone());
+                }
+            }
+            region_paths.insert(region.id.clone(), path);
+        }
+
+        *self.routing_table.write() = routing_table;
+        *self.region_paths.write() = region_paths;
+    }
+
+    /// Start a background task that polls `backend` every `interval` and
+    /// converges the peer set against what it reports: newly discovered or
+    /// changed peers are registered, and peers the backend no longer lists
+    /// are removed. Tags/labels on the discovered `Peer`s supply `Region`
+    /// and `GeoLocation`, so nearest-region and best-peer scoring keep
+    /// working without any manual `register_peer` calls.
+    pub fn start_discovery(
+        self: &Arc<Self>,
+        backend: Arc<dyn DiscoveryBackend>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match backend.discover().await {
+                    Ok(discovered) => manager.reconcile_discovered_peers(discovered),
+                    Err(e) => warn!(error = %e, "service discovery poll failed"),
+                }
+            }
+        })
+    }
+
+    /// Start a background task that probes every known peer every `interval`
+    /// via `prober`, feeding successful probes into each peer's phi-accrual
+    /// failure detector and then evaluating thresholds to transition
+    /// `PeerStatus` (or remove peers that have gone silent for too long
+    /// relative to their own heartbeat jitter).
+    pub fn start_heartbeat_monitor(
+        self: &Arc<Self>,
+        prober: Arc<dyn PeerProber>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+
+                let peer_ids: Vec<String> = manager.peers.iter().map(|e| e.key().clone()).collect();
+                for peer_id in peer_ids {
+                    let Some(peer) = manager.peers.get(&peer_id).map(|p| p.clone()) else { continue };
+                    match prober.probe(&peer).await {
+                        Ok(latency_ms) => manager.record_heartbeat_arrival(&peer_id, latency_ms),
+                        Err(e) => debug!(peer_id = %peer_id, error = %e, "heartbeat probe failed"),
+                    }
+                }
+
+                manager.evaluate_phi_thresholds();
+            }
+        })
+    }
+
+    /// Feed a successful heartbeat into `peer_id`'s failure detector and mark
+    /// it healthy with the freshly measured latency.
+    fn record_heartbeat_arrival(&self, peer_id: &str, latency_ms: u32) {
+        self.failure_detectors
+            .entry(peer_id.to_string())
+            .or_insert_with(PhiAccrualDetector::new)
+            .record_arrival();
+        self.update_peer_status(peer_id, PeerStatus::Healthy, latency_ms);
+    }
+
+    /// Walk every known peer's current phi value and transition its status:
+    /// above `phi_dead_threshold` the peer is evicted entirely, above
+    /// `phi_suspect_threshold` it is downgraded to `Degraded`.
+    fn evaluate_phi_thresholds(&self) {
+        let peer_ids: Vec<String> = self.peers.iter().map(|e| e.key().clone()).collect();
+        for peer_id in peer_ids {
+            let phi = self.failure_detectors.get(&peer_id).map(|d| d.phi()).unwrap_or(0.0);
+
+            if phi >= self.config.phi_dead_threshold {
+                warn!(peer_id = %peer_id, phi, "peer exceeded phi dead threshold, evicting");
+                self.remove_peer(&peer_id);
+                self.failure_detectors.remove(&peer_id);
+            } else if phi >= self.config.phi_suspect_threshold {
+                if let Some(latency_ms) = self.peers.get(&peer_id)
+                    .filter(|p| p.status == PeerStatus::Healthy)
+                    .map(|p| p.latency_ms)
+                {
+                    self.update_peer_status(&peer_id, PeerStatus::Degraded, latency_ms);
+                }
+            }
+        }
+    }
+
+    /// Register/update newly discovered peers and remove ones no longer reported.
+    fn reconcile_discovered_peers(&self, discovered: Vec<Peer>) {
+        let discovered_ids: std::collections::HashSet<String> =
+            discovered.iter().map(|p| p.id.clone()).collect();
+
+        for peer in discovered {
+            let changed = self.peers.get(&peer.id)
+                .map(|existing| existing.address != peer.address || existing.region.id != peer.region.id)
+                .unwrap_or(true);
+            if changed {
+                self.register_peer(peer);
+            }
+        }
+
+        let stale: Vec<String> = self.peers.iter()
+            .map(|entry| entry.key().clone())
+            .filter(|id| !discovered_ids.contains(id))
+            .collect();
+        for id in stale {
+            self.remove_peer(&id);
+        }
+    }
     
     /// Update peer status
     pub fn update_peer_status(&self, peer_id: &str, status: PeerStatus, latency_ms: u32) {
@@ -223,16 +501,25 @@ impl FederationManager {
             peer.latency_ms = latency_ms;
             peer.last_heartbeat = Instant::now();
             debug!(peer_id = %peer_id, status = ?status, latency = latency_ms, "Updated peer");
+        } else {
+            return;
         }
+        self.peer_versions.insert(peer_id.to_string(), self.next_version());
+        self.peer_record_origin.insert(peer_id.to_string(), self.config.cluster_id.clone());
+        self.recompute_routes();
     }
-    
+
     /// Update peer load
     pub fn update_peer_load(&self, peer_id: &str, current_load: u32) {
         if let Some(mut peer) = self.peers.get_mut(peer_id) {
             peer.current_load = current_load;
+        } else {
+            return;
         }
+        self.peer_versions.insert(peer_id.to_string(), self.next_version());
+        self.peer_record_origin.insert(peer_id.to_string(), self.config.cluster_id.clone());
     }
-    
+
     /// Remove a peer
     pub fn remove_peer(&self, peer_id: &str) -> bool {
         if let Some((_, peer)) = self.peers.remove(peer_id) {
@@ -240,13 +527,142 @@ impl FederationManager {
             if let Some(mut peers) = self.peers_by_region.get_mut(&peer.region.id) {
                 peers.retain(|id| id != peer_id);
             }
+            self.peer_versions.remove(peer_id);
+            self.peer_record_origin.remove(peer_id);
+            self.recompute_routes();
             info!(peer_id = %peer_id, "Removed peer");
             true
         } else {
             false
         }
     }
-    
+
+    /// Merge a batch of gossiped peer records using last-writer-wins semantics:
+    /// a record is applied if its version is newer than what we hold, or on a
+    /// tied version if its `origin_id` sorts higher than the origin of the
+    /// record we currently hold (deterministic tie-break so concurrent
+    /// writers converge on the same winner). Returns the number of records
+    /// actually applied.
+    pub fn merge_gossip(&self, records: Vec<GossipRecord>) -> usize {
+        let mut applied = 0;
+        for record in records {
+            let should_apply = match self.peer_versions.get(&record.peer_id) {
+                Some(existing_version) => {
+                    let existing_version = *existing_version;
+                    record.version > existing_version
+                        || (record.version == existing_version
+                            && self.peer_record_origin.get(&record.peer_id)
+                                .map(|origin| record.origin_id > *origin)
+                                .unwrap_or(true))
+                }
+                None => true,
+            };
+
+            if should_apply {
+                self.peer_versions.insert(record.peer_id.clone(), record.version);
+                self.peer_record_origin.insert(record.peer_id.clone(), record.origin_id.clone());
+                self.upsert_peer(record.peer);
+                applied += 1;
+            }
+        }
+        applied
+    }
+
+    /// Build a compact Bloom filter over the `(peer_id, version)` pairs this
+    /// node currently holds, to be sent to a peer as a pull request: the
+    /// responder only needs to send back records the filter doesn't contain.
+    pub fn build_pull_filter(&self) -> GossipFilter {
+        let mut filter = GossipFilter::new(self.peer_versions.len().max(1));
+        for entry in self.peer_versions.iter() {
+            filter.insert(entry.key(), *entry.value());
+        }
+        filter
+    }
+
+    /// Compute the records a remote pull filter is missing (or holds a stale
+    /// version of), to answer a pull request.
+    pub fn records_missing_from(&self, filter: &GossipFilter) -> Vec<GossipRecord> {
+        let mut out = Vec::new();
+        for entry in self.peer_versions.iter() {
+            let version = *entry.value();
+            if !filter.contains(entry.key(), version) {
+                if let Some(peer) = self.peers.get(entry.key()) {
+                    out.push(GossipRecord {
+                        peer_id: entry.key().clone(),
+                        version,
+                        peer: peer.clone(),
+                        origin_id: self.peer_record_origin.get(entry.key())
+                            .map(|o| o.clone())
+                            .unwrap_or_else(|| self.config.cluster_id.clone()),
+                    });
+                }
+            }
+        }
+        out
+    }
+
+    /// Run one gossip round: pick a random subset of healthy peers (fanout)
+    /// and prepare the push (every record this node holds, sent in full to
+    /// each target; what a target already has is not tracked, so nothing is
+    /// filtered out) and pull (our filter, so the target can reply with what
+    /// we're missing) payloads. The transport layer is responsible for actually delivering
+    /// these to the chosen peers and feeding replies into `merge_gossip`.
+    pub fn gossip_tick(&self, fanout: usize) -> GossipTick {
+        let healthy = self.get_healthy_peers();
+        let targets = Self::choose_random(&healthy, fanout);
+
+        let all_records: Vec<GossipRecord> = self.peer_versions.iter()
+            .filter_map(|entry| {
+                self.peers.get(entry.key()).map(|peer| GossipRecord {
+                    peer_id: entry.key().clone(),
+                    version: *entry.value(),
+                    peer: peer.clone(),
+                    origin_id: self.peer_record_origin.get(entry.key())
+                        .map(|o| o.clone())
+                        .unwrap_or_else(|| self.config.cluster_id.clone()),
+                })
+            })
+            .collect();
+
+        let push = targets.into_iter()
+            .map(|peer| GossipPush {
+                peer_id: peer.id.clone(),
+                address: peer.address.clone(),
+                records: all_records.clone(),
+            })
+            .collect();
+
+        GossipTick {
+            push,
+            pull_filter: self.build_pull_filter(),
+        }
+    }
+
+    /// Pick up to `n` distinct peers pseudo-randomly without pulling in an
+    /// external RNG dependency.
+    fn choose_random(peers: &[Peer], n: usize) -> Vec<Peer> {
+        if peers.is_empty() || n == 0 {
+            return Vec::new();
+        }
+        let mut seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+            ^ 0x9E3779B97F4A7C15;
+
+        let mut indices: Vec<usize> = (0..peers.len()).collect();
+        let mut chosen = Vec::with_capacity(n.min(peers.len()));
+        for _ in 0..n.min(peers.len()) {
+            // xorshift64
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let pick = (seed as usize) % indices.len();
+            chosen.push(peers[indices.remove(pick)].clone());
+        }
+        chosen
+    }
+
     /// Get best peer for a target region
     pub fn get_best_peer(&self, target_region: &str) -> Option<Peer> {
         let peer_ids = self.peers_by_region.get(target_region)?;
@@ -303,7 +719,7 @@ impl FederationManager {
         if !self.config.cross_region_routing {
             return Err(FederationError::RegionNotFound(target_region.into()));
         }
-        
+
         // Find direct peer in target region
         if let Some(peer) = self.get_best_peer(target_region) {
             return Ok(RoutingPath {
@@ -313,21 +729,42 @@ impl FederationManager {
                 is_local: false,
             });
         }
-        
-        // Check routing table for multi-hop
-        let routing_table = self.routing_table.read();
-        if let Some(next_hop) = routing_table.get(target_region) {
-            if let Some(peer) = self.peers.get(next_hop) {
-                return Ok(RoutingPath {
-                    hops: vec![peer.clone()],
-                    target_region: target_region.into(),
-                    estimated_latency_ms: peer.latency_ms,
-                    is_local: false,
-                });
-            }
+
+        // Fall back to the link-state computed multi-hop path: the chain of
+        // intermediate regions (destination excluded) that must each forward
+        // the message via their best peer before it reaches `target_region`.
+        let path = self.region_paths.read().get(target_region).cloned();
+        let Some(path) = path else {
+            return Err(FederationError::RegionNotFound(target_region.into()));
+        };
+        let intermediate = &path[..path.len().saturating_sub(1)];
+        if intermediate.is_empty() {
+            return Err(FederationError::RegionNotFound(target_region.into()));
         }
-        
-        Err(FederationError::RegionNotFound(target_region.into()))
+
+        if intermediate.len() > self.config.max_forward_hops as usize {
+            return Err(FederationError::ForwardingFailed(format!(
+                "path to {target_region} needs {} hops, exceeds max_forward_hops ({})",
+                intermediate.len(),
+                self.config.max_forward_hops
+            )));
+        }
+
+        let mut hops = Vec::with_capacity(intermediate.len());
+        let mut estimated_latency_ms: u32 = 0;
+        for region_id in intermediate {
+            let peer = self.get_best_peer(region_id)
+                .ok_or_else(|| FederationError::RegionNotFound(region_id.clone()))?;
+            estimated_latency_ms = estimated_latency_ms.saturating_add(peer.latency_ms);
+            hops.push(peer);
+        }
+
+        Ok(RoutingPath {
+            hops,
+            target_region: target_region.into(),
+            estimated_latency_ms,
+            is_local: false,
+        })
     }
     
     /// Get all healthy peers
@@ -353,7 +790,58 @@ impl FederationManager {
     pub fn list_regions(&self) -> Vec<Region> {
         self.regions.iter().map(|r| r.clone()).collect()
     }
-    
+
+    /// Get a region's current role state (defaults to `Follower` when unset,
+    /// since a region must be explicitly promoted to `Leader`).
+    pub fn get_region_role_state(&self, region_id: &str) -> RegionRoleState {
+        self.region_roles.get(region_id)
+            .map(|r| *r)
+            .unwrap_or(RegionRoleState::Follower)
+    }
+
+    /// Transition a region's role state, enforcing the only safe paths:
+    /// `Follower -> Leader` (promotion), `Leader -> Downgrading` (failover
+    /// begins), and `Downgrading -> Follower` (failover complete). Entering
+    /// `Downgrading` takes effect immediately so `should_reject_write` starts
+    /// rejecting new work the instant the last flush has been scheduled by
+    /// the caller, preventing a window where two regions both accept writes.
+    pub fn set_region_role_state_gracefully(
+        &self,
+        region_id: &str,
+        new_state: RegionRoleState,
+    ) -> Result<(), FederationError> {
+        if !self.regions.contains_key(region_id) {
+            return Err(FederationError::RegionNotFound(region_id.into()));
+        }
+
+        let current = self.get_region_role_state(region_id);
+        let allowed = matches!(
+            (current, new_state),
+            (RegionRoleState::Follower, RegionRoleState::Leader)
+                | (RegionRoleState::Leader, RegionRoleState::Downgrading)
+                | (RegionRoleState::Downgrading, RegionRoleState::Follower)
+        );
+
+        if !allowed {
+            return Err(FederationError::InvalidRoleTransition {
+                region_id: region_id.into(),
+                from: current,
+                to: new_state,
+            });
+        }
+
+        self.region_roles.insert(region_id.to_string(), new_state);
+        info!(region = %region_id, from = ?current, to = ?new_state, "Region role transitioned");
+        Ok(())
+    }
+
+    /// Whether new writes targeting this region must be rejected because it
+    /// is mid-failover. Regions with no recorded role (plain followers that
+    /// were never promoted) are not rejecting.
+    pub fn should_reject_write(&self, region_id: &str) -> bool {
+        matches!(self.get_region_role_state(region_id), RegionRoleState::Downgrading)
+    }
+
     /// Get cluster stats
     pub fn get_stats(&self) -> FederationStats {
         let total_peers = self.peers.len();
@@ -371,6 +859,81 @@ impl FederationManager {
     }
 }
 
+/// A versioned peer record exchanged during gossip (last-writer-wins CRDT)
+#[derive(Debug, Clone)]
+pub struct GossipRecord {
+    pub peer_id: String,
+    pub version: u64,
+    pub peer: Peer,
+    /// Id of the cluster that authored this version, used to break ties
+    /// between two records sharing a version number. Unlike `peer.id` (the
+    /// subject of the record, identical across every writer's copy), this
+    /// can actually differ between concurrent writers.
+    pub origin_id: String,
+}
+
+/// Outgoing push payload for a single gossip target
+#[derive(Debug, Clone)]
+pub struct GossipPush {
+    pub peer_id: String,
+    pub address: String,
+    pub records: Vec<GossipRecord>,
+}
+
+/// The push/pull payloads produced by one `gossip_tick`
+#[derive(Debug, Clone)]
+pub struct GossipTick {
+    pub push: Vec<GossipPush>,
+    pub pull_filter: GossipFilter,
+}
+
+/// Fixed-size Bloom filter over `(peer_id, version)` pairs, used so a gossip
+/// pull request doesn't have to enumerate every record the requester holds.
+#[derive(Debug, Clone)]
+pub struct GossipFilter {
+    bits: Vec<u64>,
+    num_hashes: u32,
+}
+
+impl GossipFilter {
+    /// Size the filter for roughly a 1% false-positive rate at `expected_items`.
+    fn new(expected_items: usize) -> Self {
+        let num_bits = (expected_items.max(1) * 10).next_power_of_two().max(64);
+        let num_words = num_bits / 64;
+        Self {
+            bits: vec![0u64; num_words],
+            num_hashes: 4,
+        }
+    }
+
+    fn hash(&self, peer_id: &str, version: u64, seed: u64) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        seed.hash(&mut hasher);
+        peer_id.hash(&mut hasher);
+        version.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn indices(&self, peer_id: &str, version: u64) -> impl Iterator<Item = usize> + '_ {
+        let total_bits = self.bits.len() * 64;
+        (0..self.num_hashes).map(move |i| {
+            (self.hash(peer_id, version, i as u64) as usize) % total_bits
+        })
+    }
+
+    pub fn insert(&mut self, peer_id: &str, version: u64) {
+        for idx in self.indices(peer_id, version).collect::<Vec<_>>() {
+            self.bits[idx / 64] |= 1u64 << (idx % 64);
+        }
+    }
+
+    pub fn contains(&self, peer_id: &str, version: u64) -> bool {
+        self.indices(peer_id, version)
+            .all(|idx| self.bits[idx / 64] & (1u64 << (idx % 64)) != 0)
+    }
+}
+
 /// Routing path to destination
 #[derive(Debug, Clone)]
 pub struct RoutingPath {
@@ -464,4 +1027,190 @@ mod tests {
         assert!(path.is_local);
         assert!(path.hops.is_empty());
     }
+
+    #[test]
+    fn test_gossip_merge_last_writer_wins() {
+        let config = FederationConfig::default();
+        let manager = FederationManager::new(config);
+        let region = create_test_region("us-west-1", 37.0, -122.0);
+
+        let stale = GossipRecord {
+            peer_id: "peer-1".into(),
+            version: 1,
+            peer: Peer::new("peer-1".into(), "10.0.0.1:9000".into(), region.clone()),
+            origin_id: "cluster-a".into(),
+        };
+        let mut fresh_peer = Peer::new("peer-1".into(), "10.0.0.2:9000".into(), region.clone());
+        fresh_peer.status = PeerStatus::Healthy;
+        let fresh = GossipRecord {
+            peer_id: "peer-1".into(),
+            version: 2,
+            peer: fresh_peer,
+            origin_id: "cluster-a".into(),
+        };
+
+        assert_eq!(manager.merge_gossip(vec![fresh.clone()]), 1);
+        // A stale record for the same peer must not overwrite the newer one
+        assert_eq!(manager.merge_gossip(vec![stale]), 0);
+        assert_eq!(manager.peers.get("peer-1").unwrap().address, "10.0.0.2:9000");
+    }
+
+    #[test]
+    fn test_gossip_merge_tie_break_by_origin_id() {
+        let config = FederationConfig::default();
+        let manager = FederationManager::new(config);
+        let region = create_test_region("us-west-1", 37.0, -122.0);
+
+        // Two concurrent writers both publish version 2 for the same peer,
+        // disagreeing about its address; since both refer to the same peer
+        // (`peer.id` is identical), the tie must be broken by `origin_id`,
+        // not by a comparison that always reads as false.
+        let from_cluster_a = GossipRecord {
+            peer_id: "peer-1".into(),
+            version: 2,
+            peer: Peer::new("peer-1".into(), "10.0.0.10:9000".into(), region.clone()),
+            origin_id: "cluster-a".into(),
+        };
+        let from_cluster_b = GossipRecord {
+            peer_id: "peer-1".into(),
+            version: 2,
+            peer: Peer::new("peer-1".into(), "10.0.0.20:9000".into(), region.clone()),
+            origin_id: "cluster-b".into(),
+        };
+
+        // Apply the lower-origin record first, then the higher-origin one:
+        // the higher origin must win regardless of arrival order.
+        assert_eq!(manager.merge_gossip(vec![from_cluster_a.clone()]), 1);
+        assert_eq!(manager.merge_gossip(vec![from_cluster_b.clone()]), 1);
+        assert_eq!(manager.peers.get("peer-1").unwrap().address, "10.0.0.20:9000");
+
+        // Re-applying the lower-origin record at the same version must be a
+        // no-op: the higher-origin record already won the tie.
+        assert_eq!(manager.merge_gossip(vec![from_cluster_a]), 0);
+        assert_eq!(manager.peers.get("peer-1").unwrap().address, "10.0.0.20:9000");
+    }
+
+    #[test]
+    fn test_gossip_pull_filter_roundtrip() {
+        let config = FederationConfig::default();
+        let manager = FederationManager::new(config);
+        let region = create_test_region("us-west-1", 37.0, -122.0);
+
+        manager.register_peer(Peer::new("peer-1".into(), "10.0.0.1:9000".into(), region));
+
+        let filter = manager.build_pull_filter();
+        assert!(filter.contains("peer-1", 1));
+        assert!(!filter.contains("peer-1", 2));
+        assert!(manager.records_missing_from(&GossipFilter::new(1)).len() == 1);
+    }
+
+    #[test]
+    fn test_region_failover_rejects_writes_once_downgrading() {
+        let config = FederationConfig::default();
+        let manager = FederationManager::new(config.clone());
+        let region_id = &config.region.id;
+
+        assert!(!manager.should_reject_write(region_id));
+
+        manager.set_region_role_state_gracefully(region_id, RegionRoleState::Leader).unwrap();
+        assert!(!manager.should_reject_write(region_id));
+
+        manager.set_region_role_state_gracefully(region_id, RegionRoleState::Downgrading).unwrap();
+        assert!(manager.should_reject_write(region_id));
+
+        manager.set_region_role_state_gracefully(region_id, RegionRoleState::Follower).unwrap();
+        assert!(!manager.should_reject_write(region_id));
+    }
+
+    #[test]
+    fn test_reconcile_discovered_peers_converges() {
+        let config = FederationConfig::default();
+        let manager = FederationManager::new(config);
+        let region = create_test_region("us-west-1", 37.0, -122.0);
+
+        manager.register_peer(Peer::new("peer-1".into(), "10.0.0.1:9000".into(), region.clone()));
+
+        // Backend now only reports peer-2: peer-1 should be dropped, peer-2 added.
+        manager.reconcile_discovered_peers(vec![
+            Peer::new("peer-2".into(), "10.0.0.2:9000".into(), region),
+        ]);
+
+        assert!(!manager.peers.contains_key("peer-1"));
+        assert!(manager.peers.contains_key("peer-2"));
+    }
+
+    #[test]
+    fn test_region_role_transition_rejects_skip() {
+        let config = FederationConfig::default();
+        let manager = FederationManager::new(config.clone());
+        let region_id = &config.region.id;
+
+        // Can't jump straight from Follower to Downgrading
+        let result = manager.set_region_role_state_gracefully(region_id, RegionRoleState::Downgrading);
+        assert!(matches!(result, Err(FederationError::InvalidRoleTransition { .. })));
+    }
+
+    #[test]
+    fn test_multi_hop_routing_through_intermediate_peer() {
+        let config = FederationConfig::default();
+        let manager = FederationManager::new(config);
+
+        let region_b = create_test_region("region-b", 39.0, -76.0);
+        let mut peer_b = Peer::new("peer-b".into(), "10.0.0.2:9000".into(), region_b);
+        peer_b.status = PeerStatus::Healthy;
+        manager.register_peer(peer_b);
+
+        // region-c has no registered peer, so it's only reachable by forwarding
+        // through region-b's peer.
+        let region_c = create_test_region("region-c", 39.0, -75.0);
+        manager.regions.insert(region_c.id.clone(), region_c.clone());
+        manager.recompute_routes();
+
+        let path = manager.route_to_region(&region_c.id).unwrap();
+        assert!(!path.is_local);
+        assert_eq!(path.hops.len(), 1);
+        assert_eq!(path.hops[0].id, "peer-b");
+    }
+
+    #[test]
+    fn test_multi_hop_routing_rejects_when_exceeding_max_forward_hops() {
+        let mut config = FederationConfig::default();
+        config.max_forward_hops = 0;
+        let manager = FederationManager::new(config);
+
+        let region_b = create_test_region("region-b", 39.0, -76.0);
+        let mut peer_b = Peer::new("peer-b".into(), "10.0.0.2:9000".into(), region_b);
+        peer_b.status = PeerStatus::Healthy;
+        manager.register_peer(peer_b);
+
+        let region_c = create_test_region("region-c", 39.0, -75.0);
+        manager.regions.insert(region_c.id.clone(), region_c.clone());
+        manager.recompute_routes();
+
+        let result = manager.route_to_region(&region_c.id);
+        assert!(matches!(result, Err(FederationError::ForwardingFailed(_))));
+    }
+
+    #[test]
+    fn test_phi_monitor_evicts_peer_that_goes_silent() {
+        let mut config = FederationConfig::default();
+        config.phi_suspect_threshold = 0.5;
+        config.phi_dead_threshold = 1.0;
+        let manager = FederationManager::new(config);
+        let region = create_test_region("us-west-1", 37.0, -122.0);
+        manager.register_peer(Peer::new("peer-1".into(), "10.0.0.1:9000".into(), region));
+
+        // A few regular heartbeats build up the detector's baseline jitter.
+        for _ in 0..5 {
+            manager.record_heartbeat_arrival("peer-1", 10);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(manager.peers.get("peer-1").unwrap().status, PeerStatus::Healthy);
+
+        // Once heartbeats stop arriving for long enough, phi should exceed
+        // the dead threshold and the peer should be evicted.
+        std::thread::sleep(Duration::from_millis(300));
+        manager.evaluate_phi_thresholds();
+        assert!(!manager.peers.contains_key("peer-1"));
+    }
 }
@@ -0,0 +1,138 @@
+//! Pluggable middleware chain for `AiMesh::process_message`.
+//!
+//! `process_message` hard-codes one fixed pipeline (validate, rate limit,
+//! dedup, route, store, send, budget). A [`MessageFilter`] lets third
+//! parties splice behavior into that pipeline — payload transforms, PII
+//! redaction, custom auth, audit logging — without forking the core loop.
+//! Filters are registered on `AiMesh` in an ordered `Vec<Arc<dyn
+//! MessageFilter>>` (see `AiMesh::register_filter`); ingress-side hooks run
+//! in registration order, response-side hooks run in reverse, mirroring how
+//! a request/response middleware stack unwinds.
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+use crate::protocol::{AcknowledgmentMessage, AiMessage};
+
+/// Why a filter hook failed or refused to let a message proceed.
+#[derive(Error, Debug)]
+pub enum FilterError {
+    #[error("filter '{filter}' rejected the message: {reason}")]
+    Rejected { filter: String, reason: String },
+}
+
+/// The result of an ingress-side hook (`on_ingress`/`on_pre_route`): either
+/// let the message continue down the pipeline, or short-circuit it with an
+/// acknowledgment of the filter's choosing (e.g. a policy reject or a
+/// cache hit), skipping every remaining stage including delivery.
+pub enum FilterOutcome {
+    Continue,
+    ShortCircuit(AcknowledgmentMessage),
+}
+
+/// A pluggable stage in the `process_message` pipeline.
+///
+/// Hooks default to no-ops so a filter only needs to implement the ones it
+/// cares about. `on_ingress` and `on_pre_route` can mutate the message or
+/// short-circuit the pipeline with an early acknowledgment; `on_pre_send`
+/// and `on_response` can mutate the wire payload but not abort (by the
+/// time a message is being sent, rejecting it is `on_pre_route`'s job).
+#[async_trait]
+pub trait MessageFilter: Send + Sync {
+    /// A short, human-readable name used in error messages and logs.
+    fn name(&self) -> &str;
+
+    /// Runs after `message.validate()` and the TLS identity check, before
+    /// rate limiting.
+    async fn on_ingress(&self, _message: &mut AiMessage) -> Result<FilterOutcome, FilterError> {
+        Ok(FilterOutcome::Continue)
+    }
+
+    /// Runs immediately before `router.route`, after dedup has been checked.
+    async fn on_pre_route(&self, _message: &mut AiMessage) -> Result<FilterOutcome, FilterError> {
+        Ok(FilterOutcome::Continue)
+    }
+
+    /// Runs on the serialized wire payload right before it's handed to the
+    /// transport layer (reliable send or datagram).
+    async fn on_pre_send(&self, _payload: &mut Vec<u8>) -> Result<(), FilterError> {
+        Ok(())
+    }
+
+    /// Runs on the response bytes returned by the transport layer (empty
+    /// for datagram sends), before they're cached for dedup or returned to
+    /// the caller.
+    async fn on_response(&self, _response: &mut Vec<u8>) -> Result<(), FilterError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct RejectingFilter;
+
+    #[async_trait]
+    impl MessageFilter for RejectingFilter {
+        fn name(&self) -> &str {
+            "rejecting-filter"
+        }
+
+        async fn on_ingress(&self, message: &mut AiMessage) -> Result<FilterOutcome, FilterError> {
+            Ok(FilterOutcome::ShortCircuit(AcknowledgmentMessage::failure(
+                message.message_id.clone(),
+                "blocked by policy".into(),
+            )))
+        }
+    }
+
+    struct RedactingFilter;
+
+    #[async_trait]
+    impl MessageFilter for RedactingFilter {
+        fn name(&self) -> &str {
+            "redacting-filter"
+        }
+
+        async fn on_pre_send(&self, payload: &mut Vec<u8>) -> Result<(), FilterError> {
+            payload.clear();
+            payload.extend_from_slice(b"redacted");
+            Ok(())
+        }
+
+        async fn on_response(&self, response: &mut Vec<u8>) -> Result<(), FilterError> {
+            response.extend_from_slice(b"-seen");
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_on_ingress_short_circuit_carries_the_filters_acknowledgment() {
+        let filter = RejectingFilter;
+        let mut message = AiMessage::new("agent".into(), b"payload".to_vec(), 10.0, i64::MAX);
+
+        let outcome = filter.on_ingress(&mut message).await.unwrap();
+        match outcome {
+            FilterOutcome::ShortCircuit(ack) => {
+                assert!(!ack.is_success());
+                assert_eq!(ack.error, "blocked by policy");
+            }
+            FilterOutcome::Continue => panic!("expected a short-circuit"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pre_send_and_response_hooks_mutate_in_place() {
+        let filter = RedactingFilter;
+
+        let mut payload = b"sensitive".to_vec();
+        filter.on_pre_send(&mut payload).await.unwrap();
+        assert_eq!(payload, b"redacted".to_vec());
+
+        let mut response = b"ack".to_vec();
+        filter.on_response(&mut response).await.unwrap();
+        assert_eq!(response, b"ack-seen".to_vec());
+    }
+}
@@ -3,12 +3,16 @@
 //! Latency-based geographic routing with region affinity and failover.
 
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use dashmap::DashMap;
 use tracing::{debug, info};
 
 use crate::federation::{GeoLocation, Region, Peer, FederationManager};
 use crate::routing::CostAwareRouter;
+use crate::routing::flow::MinCostFlow;
 use crate::protocol::AiMessage;
 
 /// Geo-routing configuration
@@ -28,6 +32,9 @@ pub struct GeoRoutingConfig {
     pub cost_weight: f64,
     /// Weight for load in routing decisions
     pub load_weight: f64,
+    /// How long a spooled remote delivery is retried before it's declared
+    /// permanently failed and DSN'd back to the sender.
+    pub max_retry_deadline: Duration,
 }
 
 impl Default for GeoRoutingConfig {
@@ -40,10 +47,52 @@ impl Default for GeoRoutingConfig {
             latency_weight: 0.4,
             cost_weight: 0.3,
             load_weight: 0.3,
+            max_retry_deadline: Duration::from_secs(24 * 3600),
         }
     }
 }
 
+/// A batch input to [`GeoRouter::assign_clients`]: an agent and how much
+/// regional capacity it consumes wherever it lands (e.g. concurrent
+/// streams or requests/sec), plus its location for latency scoring.
+#[derive(Debug, Clone)]
+pub struct AgentLoad {
+    pub agent_id: String,
+    pub weight: u32,
+    pub client_location: Option<GeoLocation>,
+}
+
+/// Lifecycle state of a region, as tracked by `GeoRouter` independently of
+/// raw latency measurements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionState {
+    /// Accepting new traffic normally.
+    Active,
+    /// Mid-decommission: excluded from new target selection and from
+    /// `fallback_regions`, but a client already pinned here via affinity is
+    /// only re-homed on its *next* `route` call, not mid-flight.
+    Draining,
+    /// Hard down: any route targeting it fails with
+    /// `GeoRoutingError::RegionUnavailable`.
+    Down,
+}
+
+/// Last-reported available/total capacity for a region.
+#[derive(Debug, Clone, Copy)]
+struct RegionCapacity {
+    available: u32,
+    total: u32,
+}
+
+/// Per-region state and capacity, as surfaced by `GeoRouter::get_stats`.
+#[derive(Debug, Clone)]
+pub struct RegionHealth {
+    pub region_id: String,
+    pub state: RegionState,
+    pub available_capacity: Option<u32>,
+    pub total_capacity: Option<u32>,
+}
+
 /// Geo-aware routing decision
 #[derive(Debug, Clone)]
 pub struct GeoRoutingDecision {
@@ -56,6 +105,42 @@ pub struct GeoRoutingDecision {
     pub fallback_regions: Vec<String>,
 }
 
+/// Escalating retry schedule (seconds) for spooled remote deliveries:
+/// immediate, 30s, 2m, 10m, then capped at 1h. `SpoolItem::attempts`
+/// indexes into this, saturating at the last entry.
+const RETRY_BACKOFF_SECS: &[u64] = &[0, 30, 120, 600, 3600];
+
+/// A message routed to a remote region, spooled for retry instead of
+/// attempted once inline, analogous to a mail transport agent's outbound
+/// queue.
+#[derive(Debug, Clone)]
+struct SpoolItem {
+    message: AiMessage,
+    target_region: String,
+    attempts: u32,
+    next_retry_at: std::time::Instant,
+    last_error: Option<String>,
+    first_enqueued: std::time::Instant,
+}
+
+/// Escalating backoff for a spool item's next retry, jittered by up to
+/// +/-10% (deterministically, from the message ID and attempt count) so
+/// that a batch of items delayed by the same region outage don't all wake
+/// up and retry in the same instant.
+fn backoff_with_jitter(attempts: u32, message_id: &str) -> Duration {
+    let idx = (attempts as usize).min(RETRY_BACKOFF_SECS.len() - 1);
+    let base = RETRY_BACKOFF_SECS[idx];
+    if base == 0 {
+        return Duration::from_secs(0);
+    }
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (message_id, attempts).hash(&mut hasher);
+    let jitter_pct = (hasher.finish() % 21) as i64 - 10; // -10..=10
+    let jittered = (base as i64 + base as i64 * jitter_pct / 100).max(1) as u64;
+    Duration::from_secs(jittered)
+}
+
 /// Geo-routing engine
 pub struct GeoRouter {
     config: GeoRoutingConfig,
@@ -65,6 +150,20 @@ pub struct GeoRouter {
     region_latencies: DashMap<String, LatencyStats>,
     /// Client locations for affinity
     client_regions: DashMap<String, String>,
+    /// Externally-supplied per-region cost figure (e.g. egress pricing),
+    /// used by `assign_clients`'s composite score. Unset regions cost 0.
+    region_costs: DashMap<String, f64>,
+    /// Lifecycle state per region; unset regions are `Active`.
+    region_states: DashMap<String, RegionState>,
+    /// Last-reported available/total capacity per region; unset regions are
+    /// treated as having capacity (no report yet, not necessarily empty).
+    region_capacity: DashMap<String, RegionCapacity>,
+    /// Cross-region deliveries awaiting an attempt or retry, keyed by
+    /// message ID.
+    delivery_spool: DashMap<String, SpoolItem>,
+    /// Cumulative deliveries that exceeded `max_retry_deadline` and were
+    /// DSN'd back to the sender.
+    delivery_failures: AtomicU64,
 }
 
 /// Latency statistics for a region
@@ -125,6 +224,11 @@ impl GeoRouter {
             local_router,
             region_latencies: DashMap::new(),
             client_regions: DashMap::new(),
+            region_costs: DashMap::new(),
+            region_states: DashMap::new(),
+            region_capacity: DashMap::new(),
+            delivery_spool: DashMap::new(),
+            delivery_failures: AtomicU64::new(0),
         }
     }
     
@@ -157,41 +261,110 @@ impl GeoRouter {
         self.route_to_remote_region(message, &target_region)
     }
     
-    /// Determine target region based on message metadata and client location
+    /// Determine target region based on message metadata and client location.
+    /// `Draining`/`Down` regions are never picked fresh; an agent whose
+    /// cached affinity points at one is re-homed here (the cache is
+    /// overwritten with whatever fresh target is chosen below).
     fn determine_target_region(&self, message: &AiMessage, client_location: Option<&GeoLocation>) -> Result<String, GeoRoutingError> {
         // Check if message specifies a region
         if let Some(region) = message.metadata.get("target_region") {
-            return Ok(region.clone());
+            if self.get_region_state(region) == RegionState::Down {
+                return Err(GeoRoutingError::RegionUnavailable(region.clone()));
+            }
+            if self.is_selectable(region) {
+                return Ok(region.clone());
+            }
         }
-        
-        // Check client region affinity
+
+        // Check client region affinity, re-homing away from it if it's no
+        // longer selectable.
         if self.config.region_affinity {
             if let Some(region) = self.client_regions.get(&message.agent_id) {
-                return Ok(region.clone());
+                if self.is_selectable(&region) {
+                    return Ok(region.clone());
+                }
             }
         }
-        
-        // Use client location to find nearest region
-        if let Some(location) = client_location {
-            if let Some(region) = self.federation.get_nearest_region(location) {
-                return Ok(region.id);
+
+        let fresh_target = if let Some(location) = client_location {
+            self.nearest_selectable_region(location).map(|r| r.id)
+        } else {
+            None
+        };
+
+        let target = match fresh_target {
+            Some(region) => region,
+            None => {
+                let local_region = self.federation.get_stats().local_region;
+                if !self.is_selectable(&local_region) {
+                    return Err(GeoRoutingError::RegionUnavailable(local_region));
+                }
+                local_region
             }
+        };
+
+        if self.config.region_affinity {
+            self.client_regions.insert(message.agent_id.clone(), target.clone());
         }
-        
-        // Default to local region
-        Ok(self.federation.get_stats().local_region)
+        Ok(target)
     }
-    
+
+    /// Whether `region_id` can be picked as a fresh routing target: not
+    /// `Draining`/`Down`, and not reporting zero available capacity.
+    fn is_selectable(&self, region_id: &str) -> bool {
+        if matches!(self.get_region_state(region_id), RegionState::Draining | RegionState::Down) {
+            return false;
+        }
+        self.region_capacity.get(region_id).map(|c| c.available > 0).unwrap_or(true)
+    }
+
+    /// Nearest region to `location` that's currently selectable.
+    fn nearest_selectable_region(&self, location: &GeoLocation) -> Option<Region> {
+        self.federation.list_regions()
+            .into_iter()
+            .filter(|r| self.is_selectable(&r.id))
+            .min_by(|a, b| {
+                location.distance_to(&a.location)
+                    .partial_cmp(&location.distance_to(&b.location))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
+
+    /// Set a region's lifecycle state.
+    pub fn set_region_state(&self, region_id: &str, state: RegionState) {
+        self.region_states.insert(region_id.to_string(), state);
+        info!(region = %region_id, state = ?state, "Set region state");
+    }
+
+    /// Get a region's lifecycle state (defaults to `Active` when unset).
+    pub fn get_region_state(&self, region_id: &str) -> RegionState {
+        self.region_states.get(region_id).map(|s| *s).unwrap_or(RegionState::Active)
+    }
+
+    /// Report a region's available/total capacity.
+    pub fn set_region_capacity(&self, region_id: &str, available: u32, total: u32) {
+        self.region_capacity.insert(region_id.to_string(), RegionCapacity { available, total });
+    }
+
+    /// Get a region's last-reported available/total capacity, if any.
+    pub fn get_region_capacity(&self, region_id: &str) -> Option<(u32, u32)> {
+        self.region_capacity.get(region_id).map(|c| (c.available, c.total))
+    }
+
     /// Route to a remote region
     fn route_to_remote_region(&self, message: &AiMessage, target_region: &str) -> Result<GeoRoutingDecision, GeoRoutingError> {
+        if self.get_region_state(target_region) == RegionState::Down {
+            return Err(GeoRoutingError::RegionUnavailable(target_region.into()));
+        }
+
         // Get routing path
         let path = self.federation.route_to_region(target_region)
             .map_err(|e| GeoRoutingError::RegionUnavailable(e.to_string()))?;
-        
+
         if path.hops.is_empty() {
             return Err(GeoRoutingError::NoRoute(target_region.into()));
         }
-        
+
         let peer = &path.hops[0];
         
         // Check latency constraint
@@ -207,10 +380,10 @@ impl GeoRouter {
             return self.find_fallback_region(message);
         }
         
-        // Build fallback list
+        // Build fallback list, excluding anything not selectable
         let fallback_regions: Vec<String> = self.federation.list_regions()
             .iter()
-            .filter(|r| r.id != target_region)
+            .filter(|r| r.id != target_region && self.is_selectable(&r.id))
             .map(|r| r.id.clone())
             .take(3)
             .collect();
@@ -229,10 +402,130 @@ impl GeoRouter {
         })
     }
     
+    /// Spool `message` for reliable delivery to `target_region` instead of
+    /// a single best-effort attempt: it's retried by `tick`/the background
+    /// worker on an escalating schedule until delivered or until
+    /// `max_retry_deadline` elapses, at which point a DSN is generated.
+    pub fn enqueue_remote(&self, message: AiMessage, target_region: &str) {
+        let now = std::time::Instant::now();
+        let item = SpoolItem {
+            message,
+            target_region: target_region.to_string(),
+            attempts: 0,
+            next_retry_at: now,
+            last_error: None,
+            first_enqueued: now,
+        };
+        self.delivery_spool.insert(item.message.message_id.clone(), item);
+    }
+
+    /// Attempt one delivery against the current routing path's hops:
+    /// succeeds if `path.hops[0]` resolves and is healthy, mirroring
+    /// `route_to_remote_region`'s own notion of a usable next hop.
+    fn attempt_delivery(&self, target_region: &str) -> Result<(), GeoRoutingError> {
+        let path = self.federation.route_to_region(target_region)
+            .map_err(|e| GeoRoutingError::RegionUnavailable(e.to_string()))?;
+
+        let peer = path.hops.first()
+            .ok_or_else(|| GeoRoutingError::NoRoute(target_region.into()))?;
+
+        if !peer.is_healthy() {
+            return Err(GeoRoutingError::RoutingFailed(format!("peer {} is not healthy", peer.id)));
+        }
+
+        Ok(())
+    }
+
+    /// Build a delivery-status notification `AiMessage` addressed back to
+    /// `item.message.agent_id`, reporting a permanent delivery failure.
+    fn build_dsn(&self, item: &SpoolItem, error: &str) -> AiMessage {
+        let mut dsn = AiMessage::new(
+            item.message.agent_id.clone(),
+            format!(
+                "delivery to region {} failed permanently after {} attempts: {}",
+                item.target_region, item.attempts, error
+            ).into_bytes(),
+            0.0,
+            i64::MAX,
+        );
+        dsn.metadata.insert("dsn_status".into(), "failed".into());
+        dsn.metadata.insert("dsn_original_message_id".into(), item.message.message_id.clone());
+        dsn.metadata.insert("dsn_target_region".into(), item.target_region.clone());
+        dsn
+    }
+
+    /// Drain due spool items: attempt delivery, dropping it on success or
+    /// rescheduling it on the escalating backoff on failure. Items past
+    /// `max_retry_deadline` are dropped and a DSN is returned for each, for
+    /// the caller to re-inject into the message pipeline.
+    pub fn tick(&self) -> Vec<AiMessage> {
+        let now = std::time::Instant::now();
+        let due: Vec<String> = self.delivery_spool.iter()
+            .filter(|entry| entry.next_retry_at <= now)
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        let mut dsns = Vec::new();
+        for id in due {
+            let Some((_, mut item)) = self.delivery_spool.remove(&id) else { continue };
+
+            match self.attempt_delivery(&item.target_region) {
+                Ok(()) => {
+                    debug!(message_id = %id, region = %item.target_region, "Spooled delivery succeeded");
+                }
+                Err(e) => {
+                    item.attempts += 1;
+                    item.last_error = Some(e.to_string());
+
+                    if now.duration_since(item.first_enqueued) > self.config.max_retry_deadline {
+                        self.delivery_failures.fetch_add(1, Ordering::Relaxed);
+                        info!(
+                            message_id = %id, region = %item.target_region, attempts = item.attempts,
+                            "Spooled delivery exceeded max retry deadline, emitting DSN"
+                        );
+                        dsns.push(self.build_dsn(&item, &e.to_string()));
+                    } else {
+                        item.next_retry_at = now + backoff_with_jitter(item.attempts, &id);
+                        self.delivery_spool.insert(id, item);
+                    }
+                }
+            }
+        }
+        dsns
+    }
+
+    /// Start a background task that drains due spooled deliveries every
+    /// `interval`, mirroring `SemanticDeduplicator::start_purge_task`. DSNs
+    /// produced by expired items are logged; a caller that needs to act on
+    /// them (e.g. forward to the originating agent) should call `tick`
+    /// directly instead.
+    pub fn start_delivery_worker(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let router = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let dsns = router.tick();
+                if !dsns.is_empty() {
+                    debug!(count = dsns.len(), "Spooled delivery worker emitted DSNs");
+                }
+            }
+        })
+    }
+
     /// Find a fallback region when target is unavailable
     fn find_fallback_region(&self, _message: &AiMessage) -> Result<GeoRoutingDecision, GeoRoutingError> {
         let local_region = self.federation.get_stats().local_region;
-        
+
+        // The local region is the realistic fallback target, but it's also
+        // the thing most likely to be Down/Draining when a remote region
+        // needed a fallback in the first place -- run it through the same
+        // selectability check every other region pick in this file uses
+        // instead of routing straight back into an excluded region.
+        if !self.is_selectable(&local_region) {
+            return Err(GeoRoutingError::RegionUnavailable(local_region));
+        }
+
         // Try to route locally
         // Note: This is synchronous fallback, actual routing would be async
         Ok(GeoRoutingDecision {
@@ -256,7 +549,158 @@ impl GeoRouter {
     pub fn get_client_region(&self, agent_id: &str) -> Option<String> {
         self.client_regions.get(agent_id).map(|r| r.clone())
     }
-    
+
+    /// Set the per-region cost figure used by `assign_clients`'s composite
+    /// score (e.g. egress pricing). Regions with no cost set score 0 here.
+    pub fn set_region_cost(&self, region_id: &str, cost: f64) {
+        self.region_costs.insert(region_id.to_string(), cost);
+    }
+
+    /// Solve a capacity-aware batch assignment of `agents` to regions via
+    /// min-cost max-flow, instead of `determine_target_region`'s
+    /// nearest-region-or-affinity pick, which ignores that a region can be
+    /// overloaded.
+    ///
+    /// Agents are seated one at a time, heaviest first, each against its own
+    /// flow network (one agent node -> every known region, cost = the
+    /// weighted `latency_weight`/`cost_weight`/`load_weight` composite score
+    /// -> sink, capacity = the region's remaining peer capacity after
+    /// previously-seated agents in this batch). Solving per agent, against
+    /// capacity this function tracks itself, is what keeps a single agent's
+    /// weight from being credited to one region while the solver actually
+    /// split it across two: a result spanning more than one region edge is
+    /// treated the same as "no region had room". An agent whose weight
+    /// can't be placed whole in any single region spills over to the
+    /// cheapest region by raw score if `allow_fallback` is set, or is
+    /// simply omitted from the result otherwise. Successful assignments are
+    /// persisted into `client_regions`.
+    pub fn assign_clients(&self, agents: &[AgentLoad]) -> HashMap<String, String> {
+        if agents.is_empty() {
+            return HashMap::new();
+        }
+
+        let regions = self.federation.list_regions();
+        if regions.is_empty() {
+            return HashMap::new();
+        }
+
+        let mut remaining_capacity: HashMap<&str, i64> = regions.iter()
+            .map(|r| (r.id.as_str(), self.region_capacity(&r.id)))
+            .collect();
+
+        let mut order: Vec<usize> = (0..agents.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(agents[i].weight));
+
+        let mut assignments = HashMap::new();
+        for i in order {
+            let agent = &agents[i];
+            let weight = agent.weight as i64;
+
+            let source = 0;
+            let agent_node = 1;
+            let region_base = 2;
+            let sink = region_base + regions.len();
+
+            let mut flow = MinCostFlow::new(sink + 1);
+            flow.add_edge(source, agent_node, weight, 0.0);
+            let mut region_edges = Vec::with_capacity(regions.len());
+            for (r, region) in regions.iter().enumerate() {
+                let score = self.assignment_score(region, agent.client_location.as_ref());
+                let edge_idx = flow.add_edge(agent_node, region_base + r, weight, score);
+                region_edges.push(edge_idx);
+                // Only a region with room for the whole weight gets a usable
+                // sink edge, so the cheapest such region takes all of it.
+                let remaining = remaining_capacity.get(region.id.as_str()).copied().unwrap_or(0);
+                let capacity = if remaining >= weight { weight } else { 0 };
+                flow.add_edge(region_base + r, sink, capacity, 0.0);
+            }
+
+            let pushed = flow.solve(source, sink, weight);
+            let mut seated = region_edges.iter().enumerate()
+                .filter(|(_, &edge_idx)| flow.flow_on(edge_idx) > 0);
+            let single_region = match (seated.next(), seated.next()) {
+                (Some((r, _)), None) if pushed == weight => Some(r),
+                _ => None,
+            };
+
+            let region_id = match single_region {
+                Some(r) => {
+                    *remaining_capacity.entry(regions[r].id.as_str()).or_insert(0) -= weight;
+                    Some(regions[r].id.clone())
+                }
+                // No single region had room for this agent's whole weight;
+                // spill over to whichever region scores cheapest, ignoring
+                // capacity.
+                None if self.config.allow_fallback => regions
+                    .iter()
+                    .min_by(|a, b| {
+                        self.assignment_score(a, agent.client_location.as_ref())
+                            .partial_cmp(&self.assignment_score(b, agent.client_location.as_ref()))
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    })
+                    .map(|r| r.id.clone()),
+                None => None,
+            };
+
+            if let Some(region_id) = region_id {
+                self.client_regions.insert(agent.agent_id.clone(), region_id.clone());
+                assignments.insert(agent.agent_id.clone(), region_id);
+            }
+        }
+
+        debug!(agents = agents.len(), assigned = assignments.len(), "Resolved region assignment via min-cost flow");
+        assignments
+    }
+
+    /// Composite per-(agent, region) score: `latency_weight * norm_latency +
+    /// cost_weight * region_cost + load_weight * region_utilization`, lower
+    /// is better. Mirrors `CostAwareRouter::score_endpoint`'s shape.
+    fn assignment_score(&self, region: &Region, client_location: Option<&GeoLocation>) -> f64 {
+        self.config.latency_weight * self.norm_latency(region, client_location)
+            + self.config.cost_weight * self.region_cost(&region.id)
+            + self.config.load_weight * self.region_utilization(&region.id)
+    }
+
+    /// Latency estimate for `region`, normalized against `max_latency_ms` so
+    /// it sits on a comparable scale to the other score terms. Prefers a
+    /// measured `region_latencies` average; falls back to geo distance from
+    /// the client's location when no measurement has been recorded yet.
+    fn norm_latency(&self, region: &Region, client_location: Option<&GeoLocation>) -> f64 {
+        let latency_ms = if let Some((avg_ms, _)) = self.get_latency_stats(&region.id) {
+            avg_ms as f64
+        } else if let Some(location) = client_location {
+            location.distance_to(&region.location) / 100.0
+        } else {
+            0.0
+        };
+        latency_ms / self.config.max_latency_ms.max(1) as f64
+    }
+
+    /// Externally-supplied cost figure for a region, or 0 if unset.
+    fn region_cost(&self, region_id: &str) -> f64 {
+        self.region_costs.get(region_id).map(|c| *c).unwrap_or(0.0)
+    }
+
+    /// Fraction of a region's total peer capacity currently in use.
+    fn region_utilization(&self, region_id: &str) -> f64 {
+        let peers = self.federation.get_peers_in_region(region_id);
+        if peers.is_empty() {
+            return 0.0;
+        }
+        let (capacity, load) = peers.iter()
+            .fold((0u32, 0u32), |(c, l), p| (c + p.capacity, l + p.current_load));
+        if capacity == 0 { 1.0 } else { load as f64 / capacity as f64 }
+    }
+
+    /// Remaining capacity across a region's healthy peers.
+    fn region_capacity(&self, region_id: &str) -> i64 {
+        self.federation.get_peers_in_region(region_id)
+            .iter()
+            .filter(|p| p.is_healthy())
+            .map(|p| p.capacity.saturating_sub(p.current_load) as i64)
+            .sum()
+    }
+
     /// Record latency sample for a region
     pub fn record_latency(&self, region_id: &str, latency_ms: u32) {
         let mut stats = self.region_latencies
@@ -278,10 +722,33 @@ impl GeoRouter {
     
     /// Get routing stats
     pub fn get_stats(&self) -> GeoRoutingStats {
+        let region_health = self.federation.list_regions()
+            .into_iter()
+            .map(|r| {
+                let capacity = self.region_capacity.get(&r.id).map(|c| (c.available, c.total));
+                let state = self.get_region_state(&r.id);
+                RegionHealth {
+                    region_id: r.id,
+                    state,
+                    available_capacity: capacity.map(|c| c.0),
+                    total_capacity: capacity.map(|c| c.1),
+                }
+            })
+            .collect();
+
+        let (queued, deferred) = self.delivery_spool.iter()
+            .fold((0usize, 0usize), |(q, d), entry| {
+                if entry.attempts == 0 { (q + 1, d) } else { (q, d + 1) }
+            });
+
         GeoRoutingStats {
             tracked_clients: self.client_regions.len(),
             tracked_regions: self.region_latencies.len(),
             config: self.config.clone(),
+            region_health,
+            queued,
+            deferred,
+            failed: self.delivery_failures.load(Ordering::Relaxed),
         }
     }
 }
@@ -309,14 +776,36 @@ pub struct GeoRoutingStats {
     pub tracked_clients: usize,
     pub tracked_regions: usize,
     pub config: GeoRoutingConfig,
+    pub region_health: Vec<RegionHealth>,
+    /// Spooled deliveries not yet attempted.
+    pub queued: usize,
+    /// Spooled deliveries that failed at least once and are awaiting retry.
+    pub deferred: usize,
+    /// Cumulative deliveries that exceeded `max_retry_deadline` and were DSN'd.
+    pub failed: u64,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::federation::FederationConfig;
+    use crate::federation::{FederationConfig, Peer, PeerStatus};
     use crate::routing::RouterConfig;
-    
+
+    fn region(id: &str, lat: f64, lon: f64) -> Region {
+        Region {
+            id: id.into(),
+            name: id.into(),
+            location: GeoLocation::new(lat, lon, "US", "Test"),
+        }
+    }
+
+    fn healthy_peer(id: &str, region: Region, capacity: u32) -> Peer {
+        let mut peer = Peer::new(id.into(), format!("10.0.0.1:{id}"), region);
+        peer.status = PeerStatus::Healthy;
+        peer.capacity = capacity;
+        peer
+    }
+
     #[test]
     fn test_latency_stats() {
         let mut stats = LatencyStats::default();
@@ -342,8 +831,215 @@ mod tests {
         );
         
         geo_router.set_client_region("agent-1", "us-west-1");
-        
+
         let region = geo_router.get_client_region("agent-1");
         assert_eq!(region, Some("us-west-1".to_string()));
     }
+
+    #[test]
+    fn test_assign_clients_spreads_agents_across_region_capacity() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        let region_a = region("region-a", 39.0, -77.0);
+        let region_b = region("region-b", 37.0, -122.0);
+        federation.register_peer(healthy_peer("peer-a", region_a.clone(), 1));
+        federation.register_peer(healthy_peer("peer-b", region_b.clone(), 1));
+
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let geo_router = GeoRouter::new(GeoRoutingConfig::default(), federation, router);
+
+        let agents = vec![
+            AgentLoad { agent_id: "agent-1".into(), weight: 1, client_location: None },
+            AgentLoad { agent_id: "agent-2".into(), weight: 1, client_location: None },
+        ];
+        let assignments = geo_router.assign_clients(&agents);
+
+        assert_eq!(assignments.len(), 2);
+        // Each region only has room for one agent, so they can't collide.
+        assert_ne!(assignments["agent-1"], assignments["agent-2"]);
+        assert_eq!(geo_router.get_client_region("agent-1"), assignments.get("agent-1").cloned());
+    }
+
+    #[test]
+    fn test_assign_clients_falls_back_when_no_region_has_capacity() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        let region_a = region("region-a", 39.0, -77.0);
+        federation.register_peer(healthy_peer("peer-a", region_a, 0));
+
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let geo_router = GeoRouter::new(GeoRoutingConfig::default(), federation, router);
+
+        let agents = vec![AgentLoad { agent_id: "agent-1".into(), weight: 1, client_location: None }];
+        let assignments = geo_router.assign_clients(&agents);
+
+        // `allow_fallback` defaults to true, so the agent still lands
+        // somewhere even though total capacity was exhausted.
+        assert_eq!(assignments.len(), 1);
+        assert!(assignments.contains_key("agent-1"));
+    }
+
+    #[test]
+    fn test_assign_clients_does_not_split_an_oversized_agent_across_regions() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        let region_a = region("region-a", 39.0, -77.0);
+        let region_b = region("region-b", 37.0, -122.0);
+        federation.register_peer(healthy_peer("peer-a", region_a, 3));
+        federation.register_peer(healthy_peer("peer-b", region_b, 3));
+
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        // No single region can hold all 5 units of weight, but the combined
+        // capacity across both regions (6) can -- this is exactly the case
+        // the old shared flow graph would silently split across two edges.
+        let geo_router = GeoRouter::new(GeoRoutingConfig::default(), federation, router);
+        let agents = vec![AgentLoad { agent_id: "agent-1".into(), weight: 5, client_location: None }];
+        let assignments = geo_router.assign_clients(&agents);
+
+        // `allow_fallback` defaults to true: since no single region had room
+        // for the whole agent, it spills over whole to one region rather
+        // than being split between them.
+        assert_eq!(assignments.len(), 1);
+        let region_id = &assignments["agent-1"];
+        assert!(region_id == "region-a" || region_id == "region-b");
+    }
+
+    #[test]
+    fn test_assign_clients_omits_oversized_agent_when_fallback_disabled() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        let region_a = region("region-a", 39.0, -77.0);
+        let region_b = region("region-b", 37.0, -122.0);
+        federation.register_peer(healthy_peer("peer-a", region_a, 3));
+        federation.register_peer(healthy_peer("peer-b", region_b, 3));
+
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let config = GeoRoutingConfig { allow_fallback: false, ..GeoRoutingConfig::default() };
+        let geo_router = GeoRouter::new(config, federation, router);
+        let agents = vec![AgentLoad { agent_id: "agent-1".into(), weight: 5, client_location: None }];
+        let assignments = geo_router.assign_clients(&agents);
+
+        // With fallback disabled, an agent that doesn't fit whole in any
+        // single region is left unplaced rather than split across regions.
+        assert!(assignments.is_empty());
+    }
+
+    #[test]
+    fn test_draining_region_is_excluded_and_affinity_client_is_rehomed() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let geo_router = GeoRouter::new(GeoRoutingConfig::default(), federation, router);
+
+        geo_router.set_client_region("agent-1", "us-west-1");
+        assert_eq!(geo_router.get_region_state("us-west-1"), RegionState::Active);
+
+        geo_router.set_region_state("us-west-1", RegionState::Draining);
+
+        let msg = AiMessage::new("agent-1".into(), b"hi".to_vec(), 10.0, i64::MAX);
+        let target = geo_router.determine_target_region(&msg, None).unwrap();
+
+        // Re-homed away from the draining region, to the local region.
+        assert_ne!(target, "us-west-1");
+        assert_eq!(geo_router.get_client_region("agent-1"), Some(target));
+    }
+
+    #[test]
+    fn test_down_region_is_hard_excluded() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let geo_router = GeoRouter::new(GeoRoutingConfig::default(), federation, router);
+
+        let local_region = geo_router.federation.get_stats().local_region;
+        geo_router.set_region_state(&local_region, RegionState::Down);
+
+        let msg = AiMessage::new("agent-1".into(), b"hi".to_vec(), 10.0, i64::MAX);
+        let result = geo_router.determine_target_region(&msg, None);
+        assert!(matches!(result, Err(GeoRoutingError::RegionUnavailable(_))));
+    }
+
+    #[test]
+    fn test_find_fallback_region_rejects_down_local_region() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let geo_router = GeoRouter::new(GeoRoutingConfig::default(), federation, router);
+
+        let local_region = geo_router.federation.get_stats().local_region;
+        geo_router.set_region_state(&local_region, RegionState::Down);
+
+        // A remote region's latency-exceeded fallback must not route back
+        // into a local region that's itself excluded.
+        let msg = AiMessage::new("agent-1".into(), b"hi".to_vec(), 10.0, i64::MAX);
+        let result = geo_router.find_fallback_region(&msg);
+        assert!(matches!(result, Err(GeoRoutingError::RegionUnavailable(_))));
+    }
+
+    #[test]
+    fn test_get_stats_reports_region_health() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        let local_region = federation.get_stats().local_region;
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let geo_router = GeoRouter::new(GeoRoutingConfig::default(), federation, router);
+
+        geo_router.set_region_state(&local_region, RegionState::Draining);
+        geo_router.set_region_capacity(&local_region, 2, 10);
+
+        let stats = geo_router.get_stats();
+        let health = stats.region_health.iter().find(|r| r.region_id == local_region).unwrap();
+        assert_eq!(health.state, RegionState::Draining);
+        assert_eq!(health.available_capacity, Some(2));
+        assert_eq!(health.total_capacity, Some(10));
+    }
+
+    #[test]
+    fn test_spooled_delivery_succeeds_against_healthy_peer() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        let region_b = region("region-b", 37.0, -122.0);
+        federation.register_peer(healthy_peer("peer-b", region_b, 10));
+
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let geo_router = GeoRouter::new(GeoRoutingConfig::default(), federation, router);
+
+        let msg = AiMessage::new("agent-1".into(), b"hi".to_vec(), 10.0, i64::MAX);
+        geo_router.enqueue_remote(msg, "region-b");
+
+        let dsns = geo_router.tick();
+        assert!(dsns.is_empty());
+        let stats = geo_router.get_stats();
+        assert_eq!(stats.queued, 0);
+        assert_eq!(stats.deferred, 0);
+    }
+
+    #[test]
+    fn test_spooled_delivery_reschedules_on_failure_without_dsn() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let geo_router = GeoRouter::new(GeoRoutingConfig::default(), federation, router);
+
+        // No peer registered for "region-missing", so every attempt fails.
+        let msg = AiMessage::new("agent-1".into(), b"hi".to_vec(), 10.0, i64::MAX);
+        geo_router.enqueue_remote(msg, "region-missing");
+
+        let dsns = geo_router.tick();
+        assert!(dsns.is_empty());
+        let stats = geo_router.get_stats();
+        assert_eq!(stats.deferred, 1);
+        assert_eq!(stats.failed, 0);
+    }
+
+    #[test]
+    fn test_spooled_delivery_emits_dsn_after_max_retry_deadline() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let mut config = GeoRoutingConfig::default();
+        config.max_retry_deadline = Duration::from_secs(0);
+        let geo_router = GeoRouter::new(config, federation, router);
+
+        let msg = AiMessage::new("agent-1".into(), b"hi".to_vec(), 10.0, i64::MAX);
+        geo_router.enqueue_remote(msg, "region-missing");
+
+        let dsns = geo_router.tick();
+        assert_eq!(dsns.len(), 1);
+        assert_eq!(dsns[0].agent_id, "agent-1");
+        assert_eq!(dsns[0].metadata.get("dsn_status").map(String::as_str), Some("failed"));
+
+        let stats = geo_router.get_stats();
+        assert_eq!(stats.failed, 1);
+        assert_eq!(stats.deferred, 0);
+    }
 }
@@ -17,15 +17,28 @@ pub mod transport;
 pub mod ratelimit;
 pub mod tenant;
 pub mod priority;
+pub mod federation;
+pub mod filter;
+pub mod georouting;
 
 use std::sync::Arc;
+use parking_lot::RwLock;
 use thiserror::Error;
 
 pub use protocol::*;
-pub use routing::{CostAwareRouter, RouterConfig, RoutingError};
-pub use storage::{StorageLayer, StorageConfig, StorageBackend, StorageError};
+pub use routing::{
+    CostAwareRouter, RouterConfig, RoutingError, WeightTunerConfig,
+    BudgetCertifier, BudgetSnapshot, CertifyError, InMemoryBudgetCertifier,
+    EndpointGossipRecord, EndpointVersion, GossipError, GossipTransport,
+};
+pub use storage::{StorageLayer, StorageConfig, StorageBackend, StorageBackendKind, StorageEngine, StorageError};
 pub use observability::ObservabilityLayer;
-pub use ratelimit::{RateLimiter, RateLimitConfig, RateLimitError};
+pub use ratelimit::{RateLimiter, RateLimitConfig, RateLimitError, TokenType};
+pub use tenant::{TenantManager, TenantError};
+pub use transport::{TransportLayer, TransportConfig, TransportError};
+pub use filter::{MessageFilter, FilterOutcome, FilterError};
+pub use federation::{FederationManager, FederationConfig, FederationError};
+pub use georouting::{GeoRouter, GeoRoutingConfig, GeoRoutingError};
 
 /// AiMesh errors
 #[derive(Error, Debug)]
@@ -42,6 +55,10 @@ pub enum AiMeshError {
     RateLimit(#[from] ratelimit::RateLimitError),
     #[error("Configuration error: {0}")]
     Config(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(#[from] tenant::TenantError),
+    #[error("Filter error: {0}")]
+    Filter(#[from] filter::FilterError),
 }
 
 /// AiMesh configuration
@@ -61,6 +78,13 @@ pub struct AiMeshConfig {
     pub dedup_ttl_secs: u64,
     /// Enable rate limiting
     pub enable_rate_limit: bool,
+    /// QUIC transport configuration
+    pub transport: TransportConfig,
+    /// Multi-region federation configuration (peer discovery, cross-region
+    /// routing table)
+    pub federation: FederationConfig,
+    /// Geo-routing configuration, layered over the federation manager
+    pub geo_routing: GeoRoutingConfig,
 }
 
 impl Default for AiMeshConfig {
@@ -73,6 +97,9 @@ impl Default for AiMeshConfig {
             enable_dedup: true,
             dedup_ttl_secs: 3600,
             enable_rate_limit: true,
+            transport: TransportConfig::default(),
+            federation: FederationConfig::default(),
+            geo_routing: GeoRoutingConfig::default(),
         }
     }
 }
@@ -84,6 +111,17 @@ pub struct AiMesh {
     pub storage: Arc<StorageLayer>,
     pub observability: Arc<ObservabilityLayer>,
     pub rate_limiter: Arc<RateLimiter>,
+    pub tenants: Arc<TenantManager>,
+    pub transport: Arc<TransportLayer>,
+    pub federation: Arc<FederationManager>,
+    /// Cross-region routing layer, consulted by callers that need to place
+    /// traffic or clients across regions (see `GeoRouter::route`,
+    /// `assign_clients`); not on the `process_message` hot path, which
+    /// routes locally via `router`.
+    pub geo_router: Arc<GeoRouter>,
+    /// Ordered middleware chain run by `process_message`; see
+    /// `register_filter`.
+    filters: RwLock<Vec<Arc<dyn MessageFilter>>>,
 }
 
 impl AiMesh {
@@ -93,28 +131,73 @@ impl AiMesh {
         let storage = Arc::new(StorageLayer::new(config.storage.clone())?);
         let observability = Arc::new(ObservabilityLayer::new());
         let rate_limiter = Arc::new(RateLimiter::new(config.rate_limit.clone()));
-        
+        let tenants = Arc::new(TenantManager::new());
+        let transport = Arc::new(
+            TransportLayer::new(config.transport.clone())
+                .map_err(|e| AiMeshError::Transport(e.to_string()))?,
+        );
+        let federation = Arc::new(FederationManager::new(config.federation.clone()));
+        let geo_router = Arc::new(GeoRouter::new(
+            config.geo_routing.clone(),
+            federation.clone(),
+            router.clone(),
+        ));
+
         Ok(Self {
             config,
             router,
             storage,
             observability,
             rate_limiter,
+            tenants,
+            transport,
+            federation,
+            geo_router,
+            filters: RwLock::new(Vec::new()),
         })
     }
-    
-    /// Process a message through the queue
-    pub async fn process_message(&self, message: AiMessage) -> Result<AcknowledgmentMessage, AiMeshError> {
+
+    /// Append a middleware stage to the `process_message` pipeline. Filters
+    /// run in registration order on ingress and reverse order on response.
+    pub fn register_filter(&self, filter: Arc<dyn MessageFilter>) {
+        self.filters.write().push(filter);
+    }
+
+    /// Process a message through the queue. `peer_identity` is the TLS
+    /// identity the sending connection authenticated with (see
+    /// `TransportLayer::peer_identity`), if any; when present it's checked
+    /// against whatever identity `message.agent_id` is bound to via
+    /// `TenantManager::verify_identity` before the message is routed.
+    pub async fn process_message(
+        &self,
+        mut message: AiMessage,
+        peer_identity: Option<&str>,
+    ) -> Result<AcknowledgmentMessage, AiMeshError> {
         let start = std::time::Instant::now();
-        
+
+        // 0. Snapshot the filter chain once, up front, so the rest of this
+        // call never holds the lock across an await point.
+        let filters = self.filters.read().clone();
+
         // 1. Validate message
         message.validate()?;
-        
-        // 2. Check rate limit
+
+        // 1b. Verify the connection's TLS identity matches the claimed agent
+        if let Some(identity) = peer_identity {
+            self.tenants.verify_identity(&message.agent_id, identity)?;
+        }
+
+        for f in &filters {
+            if let FilterOutcome::ShortCircuit(ack) = f.on_ingress(&mut message).await? {
+                return Ok(ack);
+            }
+        }
+
+        // 2. Check rate limit (operations + bandwidth)
         if self.config.enable_rate_limit {
-            self.rate_limiter.acquire(&message.agent_id)?;
+            self.rate_limiter.acquire_message(&message.agent_id, &message)?;
         }
-        
+
         // 2. Check for duplicates (if enabled)
         if self.config.enable_dedup {
             let hash = compute_dedup_hash(&message);
@@ -127,25 +210,90 @@ impl AiMesh {
                 ));
             }
         }
-        
+
+        for f in &filters {
+            if let FilterOutcome::ShortCircuit(ack) = f.on_pre_route(&mut message).await? {
+                return Ok(ack);
+            }
+        }
+
         // 3. Route the message
         let routing_start = std::time::Instant::now();
         let decision = self.router.route(&message).await?;
         self.observability.record_routing_latency(routing_start.elapsed().as_micros() as f64);
-        
+
         // 4. Store the message
         self.storage.write_message(&message).await?;
-        
-        // 5. TODO: Send to target endpoint via QUIC transport
-        // For now, return a placeholder acknowledgment
-        let result = vec![]; // Placeholder for actual response
-        
-        // 6. Cache for dedup
-        if self.config.enable_dedup {
+
+        // 5. Send to the target endpoint via QUIC transport. Low-priority
+        // messages go out as unreliable fire-and-forget datagrams; everyone
+        // else gets a reliable stream with an awaited response.
+        let reliable = priority::PriorityLevel::from(message.priority) != priority::PriorityLevel::Low;
+        let mut payload = message.serialize()?;
+        for f in &filters {
+            f.on_pre_send(&mut payload).await?;
+        }
+
+        // `decision.target_endpoint` is the router's opaque `endpoint_id`,
+        // not a dialable address -- resolve it before handing it to the
+        // transport layer, which parses its address argument as a
+        // `SocketAddr`.
+        let target_addr = match self.transport.resolve_endpoint(&decision.target_endpoint) {
+            Ok(addr) => addr,
+            Err(e) => {
+                let latency_ms = start.elapsed().as_millis() as f64;
+                self.observability.record_message(
+                    &message.agent_id,
+                    false,
+                    latency_ms,
+                    decision.estimated_cost,
+                    decision.estimated_cost * 0.001,
+                );
+                return Err(AiMeshError::Transport(e.to_string()));
+            }
+        };
+
+        let mut result = if reliable {
+            match self.transport.send(&target_addr, payload).await {
+                Ok(response) => response,
+                Err(e) => {
+                    let latency_ms = start.elapsed().as_millis() as f64;
+                    self.observability.record_message(
+                        &message.agent_id,
+                        false,
+                        latency_ms,
+                        decision.estimated_cost,
+                        decision.estimated_cost * 0.001,
+                    );
+                    return Err(AiMeshError::Transport(e.to_string()));
+                }
+            }
+        } else {
+            if let Err(e) = self.transport.send_datagram(&target_addr, payload).await {
+                let latency_ms = start.elapsed().as_millis() as f64;
+                self.observability.record_message(
+                    &message.agent_id,
+                    false,
+                    latency_ms,
+                    decision.estimated_cost,
+                    decision.estimated_cost * 0.001,
+                );
+                return Err(AiMeshError::Transport(e.to_string()));
+            }
+            Vec::new()
+        };
+
+        for f in filters.iter().rev() {
+            f.on_response(&mut result).await?;
+        }
+
+        // 6. Cache for dedup (skipped for datagram sends: there's no
+        // downstream response to cache against a repeat)
+        if reliable && self.config.enable_dedup {
             let hash = compute_dedup_hash(&message);
             self.storage.write_dedup(&hash, result.clone());
         }
-        
+
         // 7. Update budget
         self.router.consume_budget(&message.agent_id, decision.estimated_cost)?;
         
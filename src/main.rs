@@ -26,6 +26,7 @@ async fn main() -> anyhow::Result<()> {
             dedup_collection: "aimesh_dedup".into(),
             embedding_dim: 384,
             dedup_ttl_secs: 3600,
+            ..Default::default()
         },
         ..Default::default()
     };
@@ -45,7 +46,10 @@ async fn main() -> anyhow::Result<()> {
         Err(e) => info!("Warning: Could not connect to Barq: {}", e),
     }
     
-    // Register AI model endpoints
+    // Register AI model endpoints. `endpoint_id` is the logical name the
+    // router scores and picks between; `register_endpoint_address` maps it
+    // to the local model-proxy sidecar that actually dials out to the
+    // provider, since `EndpointMetrics` carries no address of its own.
     mesh.router.register_endpoint(aimesh::EndpointMetrics {
         endpoint_id: "openai-gpt4".into(),
         capacity: 1000,
@@ -56,7 +60,8 @@ async fn main() -> anyhow::Result<()> {
         last_health_check: 0,
         health_status: aimesh::HealthStatus::Healthy as i32,
     });
-    
+    mesh.transport.register_endpoint_address("openai-gpt4", "127.0.0.1:7001");
+
     mesh.router.register_endpoint(aimesh::EndpointMetrics {
         endpoint_id: "anthropic-claude".into(),
         capacity: 1000,
@@ -67,7 +72,8 @@ async fn main() -> anyhow::Result<()> {
         last_health_check: 0,
         health_status: aimesh::HealthStatus::Healthy as i32,
     });
-    
+    mesh.transport.register_endpoint_address("anthropic-claude", "127.0.0.1:7002");
+
     mesh.router.register_endpoint(aimesh::EndpointMetrics {
         endpoint_id: "local-llama".into(),
         capacity: 100,
@@ -78,7 +84,8 @@ async fn main() -> anyhow::Result<()> {
         last_health_check: 0,
         health_status: aimesh::HealthStatus::Healthy as i32,
     });
-    
+    mesh.transport.register_endpoint_address("local-llama", "127.0.0.1:7003");
+
     info!("Registered 3 AI model endpoints");
     
     // Set budget for demo agent
@@ -92,7 +99,7 @@ async fn main() -> anyhow::Result<()> {
         i64::MAX,
     );
     
-    match mesh.process_message(test_msg).await {
+    match mesh.process_message(test_msg, None).await {
         Ok(ack) => {
             info!(
                 message_id = %ack.original_message_id,
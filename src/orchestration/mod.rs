@@ -2,16 +2,24 @@
 //!
 //! Scatter-gather task orchestration with dependency resolution.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use dashmap::DashMap;
 use thiserror::Error;
+use tokio::task::JoinSet;
 use tracing::{debug, info, warn};
 
-use crate::protocol::{AiMessage, TaskState, TaskStep, TaskStatus};
+use crate::federation::FederationManager;
+use crate::protocol::{AiMessage, RoutingDecision, TaskState, TaskStep, TaskStatus};
 use crate::routing::CostAwareRouter;
 use crate::storage::StorageLayer;
 
+mod replication;
+pub use replication::{
+    InMemoryReplicaTransport, ReplicaTransport, ReplicationConfig, ReplicationError,
+    ReplicationManager, ReplicationMode,
+};
+
 #[derive(Error, Debug)]
 pub enum OrchestrationError {
     #[error("Task not found: {0}")]
@@ -24,6 +32,28 @@ pub enum OrchestrationError {
     TaskFailed(String),
     #[error("Storage error: {0}")]
     Storage(#[from] crate::storage::StorageError),
+    #[error("Region {0} is downgrading and is not accepting new work")]
+    RegionDowngrading(String),
+    #[error("Cyclic dependency detected among steps: {0:?}")]
+    CyclicDependency(Vec<String>),
+    #[error("Step '{step_id}' depends on unknown step '{dependency}'")]
+    UnknownDependency { step_id: String, dependency: String },
+    #[error("Replication error: {0}")]
+    Replication(#[from] ReplicationError),
+}
+
+/// Configuration for the orchestration engine
+#[derive(Debug, Clone)]
+pub struct OrchestrationConfig {
+    /// Maximum routing retries per step, after the first attempt, before the
+    /// task is marked failed
+    pub max_step_retries: u32,
+}
+
+impl Default for OrchestrationConfig {
+    fn default() -> Self {
+        Self { max_step_retries: 3 }
+    }
 }
 
 /// Task dependency graph
@@ -45,21 +75,136 @@ pub struct OrchestrationEngine {
     tasks: DashMap<String, TaskState>,
     storage: Arc<StorageLayer>,
     router: Arc<CostAwareRouter>,
+    federation: Arc<FederationManager>,
+    config: OrchestrationConfig,
+    replication: ReplicationManager,
 }
 
 impl OrchestrationEngine {
-    pub fn new(storage: Arc<StorageLayer>, router: Arc<CostAwareRouter>) -> Self {
+    pub fn new(
+        storage: Arc<StorageLayer>,
+        router: Arc<CostAwareRouter>,
+        federation: Arc<FederationManager>,
+        config: OrchestrationConfig,
+        replication_config: ReplicationConfig,
+    ) -> Self {
+        let replication = ReplicationManager::new(Arc::clone(&federation), replication_config);
         Self {
             tasks: DashMap::new(),
             storage,
             router,
+            federation,
+            config,
+            replication,
         }
     }
-    
+
+    /// Replay `TaskState`s recovered from peer replicas into the local task
+    /// table, e.g. after this region is promoted to `Leader` during a
+    /// failover. Returns the number of tasks recovered that were not already
+    /// known locally; existing local state always wins.
+    pub async fn recover_from_replicas(&self) -> usize {
+        let recovered = self.replication.reload_outstanding_tasks().await;
+        let mut applied = 0;
+        for (task_id, state) in recovered {
+            if !self.tasks.contains_key(&task_id) {
+                self.tasks.insert(task_id.clone(), state);
+                applied += 1;
+                info!(task_id = %task_id, "Recovered task state from replica");
+            }
+        }
+        applied
+    }
+
+    /// The target region for a step, if it pins one via `target_region` metadata.
+    fn step_target_region(message: &AiMessage) -> Option<&str> {
+        message.metadata.get("target_region").map(|s| s.as_str())
+    }
+
+    /// Check that every `dependencies` entry refers to a step that actually
+    /// exists in `steps`. A dangling reference (typo'd or removed step id)
+    /// would otherwise leave that dependent's in-degree permanently above
+    /// zero in `detect_cycle`, misreporting a missing dependency as a cycle.
+    fn validate_dependencies(steps: &[TaskStepDef]) -> Result<(), OrchestrationError> {
+        let known: std::collections::HashSet<&str> =
+            steps.iter().map(|s| s.step_id.as_str()).collect();
+        for step in steps {
+            for dep in &step.dependencies {
+                if !known.contains(dep.as_str()) {
+                    return Err(OrchestrationError::UnknownDependency {
+                        step_id: step.step_id.clone(),
+                        dependency: dep.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Kahn's algorithm over the step dependency graph. Returns the ids of
+    /// steps still unprocessed once no more zero-in-degree nodes remain,
+    /// i.e. the steps participating in a cycle, or `None` if the graph is a
+    /// DAG.
+    fn detect_cycle(steps: &[TaskStepDef]) -> Option<Vec<String>> {
+        let mut in_degree: HashMap<&str, usize> = steps.iter()
+            .map(|s| (s.step_id.as_str(), 0usize))
+            .collect();
+        let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+
+        for step in steps {
+            for dep in &step.dependencies {
+                *in_degree.entry(step.step_id.as_str()).or_insert(0) += 1;
+                dependents.entry(dep.as_str()).or_default().push(step.step_id.as_str());
+            }
+        }
+
+        let mut queue: VecDeque<&str> = in_degree.iter()
+            .filter(|(_, deg)| **deg == 0)
+            .map(|(id, _)| *id)
+            .collect();
+
+        let mut visited = 0usize;
+        while let Some(id) = queue.pop_front() {
+            visited += 1;
+            if let Some(deps) = dependents.get(id) {
+                for &dependent in deps {
+                    let degree = in_degree.get_mut(dependent).unwrap();
+                    *degree -= 1;
+                    if *degree == 0 {
+                        queue.push_back(dependent);
+                    }
+                }
+            }
+        }
+
+        if visited == steps.len() {
+            None
+        } else {
+            Some(in_degree.into_iter()
+                .filter(|(_, deg)| *deg > 0)
+                .map(|(id, _)| id.to_string())
+                .collect())
+        }
+    }
+
     /// Begin a new task from a dependency graph
     pub async fn begin_task(&self, graph: TaskGraph) -> Result<String, OrchestrationError> {
         let task_id = graph.task_id.clone();
-        
+
+        Self::validate_dependencies(&graph.steps)?;
+
+        if let Some(cyclic) = Self::detect_cycle(&graph.steps) {
+            return Err(OrchestrationError::CyclicDependency(cyclic));
+        }
+
+        for def in &graph.steps {
+            if let Some(region) = Self::step_target_region(&def.message) {
+                if self.federation.should_reject_write(region) {
+                    return Err(OrchestrationError::RegionDowngrading(region.to_string()));
+                }
+            }
+        }
+
         let steps: Vec<TaskStep> = graph.steps.iter().map(|def| {
             TaskStep {
                 step_id: def.step_id.clone(),
@@ -83,52 +228,176 @@ impl OrchestrationEngine {
         
         self.tasks.insert(task_id.clone(), state.clone());
         self.storage.write_task_state(&task_id, &state).await?;
-        
+        // The task is already admitted and durable in local storage at this
+        // point, so a replication shortfall must not fail the caller -- that
+        // would claim nothing happened when it did. Surface it as a warning
+        // instead; `recover_from_replicas` simply won't see this task if a
+        // region fails over before replication catches up.
+        if let Err(err) = self.replication.replicate_task_state(&task_id, &state).await {
+            warn!(task_id = %task_id, error = %err, "Failed to replicate task state to peers");
+        }
+
         info!(task_id = %task_id, steps = graph.steps.len(), "Started task");
         Ok(task_id)
     }
     
-    /// Execute the next ready step(s) in a task
+    /// Execute the next ready step(s) in a task.
+    ///
+    /// All steps that became ready (pending, with every dependency already
+    /// completed) are dispatched concurrently through the router, each
+    /// retried up to `config.max_step_retries` times on a routing error. A
+    /// step that exhausts its retries fails the whole task; steps already
+    /// in flight are still allowed to finish so partial results are kept.
     pub async fn execute_next(&self, task_id: &str) -> Result<Vec<String>, OrchestrationError> {
-        let mut task = self.tasks
-            .get_mut(task_id)
-            .ok_or_else(|| OrchestrationError::TaskNotFound(task_id.into()))?;
-        
-        // Find steps that are ready (pending with all deps satisfied)
-        let ready_steps: Vec<usize> = task.steps.iter().enumerate()
-            .filter(|(_, step)| {
-                step.status == TaskStatus::TaskPending as i32 &&
-                step.dependencies.iter().all(|dep| {
-                    task.steps.iter().any(|s| s.step_id == *dep && s.status == TaskStatus::TaskCompleted as i32)
+        let ready_steps: Vec<(usize, String, AiMessage)> = {
+            let mut task = self.tasks
+                .get_mut(task_id)
+                .ok_or_else(|| OrchestrationError::TaskNotFound(task_id.into()))?;
+
+            // Find steps that are ready (pending with all deps satisfied)
+            let ready: Vec<usize> = task.steps.iter().enumerate()
+                .filter(|(_, step)| {
+                    step.status == TaskStatus::TaskPending as i32 &&
+                    step.dependencies.iter().all(|dep| {
+                        task.steps.iter().any(|s| s.step_id == *dep && s.status == TaskStatus::TaskCompleted as i32)
+                    })
                 })
-            })
-            .map(|(i, _)| i)
-            .collect();
-        
+                .map(|(i, _)| i)
+                .collect();
+
+            // Bounce any ready step whose target region started downgrading
+            // after the task was admitted, rather than routing into a region
+            // that is mid-failover.
+            for &idx in &ready {
+                if let Some(message) = &task.steps[idx].message {
+                    if let Some(region) = Self::step_target_region(message) {
+                        if self.federation.should_reject_write(region) {
+                            return Err(OrchestrationError::RegionDowngrading(region.to_string()));
+                        }
+                    }
+                }
+            }
+
+            ready.into_iter()
+                .map(|idx| {
+                    task.steps[idx].status = TaskStatus::TaskRunning as i32;
+                    let step_id = task.steps[idx].step_id.clone();
+                    let mut message = task.steps[idx].message.clone()
+                        .expect("pending step must carry a message");
+                    // Feed each completed dependency's recorded result into
+                    // the dependent step's metadata. That result is the id
+                    // of the endpoint the dependency was routed to; no
+                    // downstream response is collected here.
+                    for dep in &task.steps[idx].dependencies {
+                        if let Some(result) = task.results.get(dep) {
+                            message.metadata.insert(
+                                format!("dependency_result:{dep}"),
+                                String::from_utf8_lossy(result).into_owned(),
+                            );
+                        }
+                    }
+                    (idx, step_id, message)
+                })
+                .collect()
+        };
+
+        let mut join_set = JoinSet::new();
+        for (idx, step_id, message) in ready_steps {
+            let router = Arc::clone(&self.router);
+            let max_retries = self.config.max_step_retries;
+            join_set.spawn(async move {
+                let outcome = Self::dispatch_step(&router, &message, max_retries).await;
+                (idx, step_id, outcome)
+            });
+        }
+
         let mut executed = Vec::new();
-        
-        for idx in ready_steps {
-            let step_id = task.steps[idx].step_id.clone();
-            task.steps[idx].status = TaskStatus::TaskRunning as i32;
-            
-            // TODO: Actually route and execute the message
-            // For now, mark as completed with empty result
-            task.steps[idx].status = TaskStatus::TaskCompleted as i32;
-            task.steps[idx].result = Vec::new();
-            
-            executed.push(step_id);
+        let mut failure: Option<String> = None;
+
+        while let Some(joined) = join_set.join_next().await {
+            let (idx, step_id, outcome) = joined
+                .map_err(|e| OrchestrationError::TaskFailed(format!("step task panicked: {e}")))?;
+
+            let mut task = self.tasks
+                .get_mut(task_id)
+                .ok_or_else(|| OrchestrationError::TaskNotFound(task_id.into()))?;
+
+            match outcome {
+                Ok(decision) => {
+                    debug!(task_id = %task_id, step_id = %step_id, endpoint = %decision.target_endpoint, "Step dispatched");
+                    task.steps[idx].status = TaskStatus::TaskCompleted as i32;
+                    task.steps[idx].result = decision.target_endpoint.clone().into_bytes();
+                    task.results.insert(step_id.clone(), decision.target_endpoint.into_bytes());
+                    executed.push(step_id);
+                }
+                Err(err) => {
+                    warn!(task_id = %task_id, step_id = %step_id, error = %err, "Step exhausted retries");
+                    task.steps[idx].status = TaskStatus::TaskFailed as i32;
+                    task.steps[idx].error = err.to_string();
+                    failure.get_or_insert(err.to_string());
+                }
+            }
         }
-        
-        // Check if all steps are complete
-        let all_complete = task.steps.iter().all(|s| s.status == TaskStatus::TaskCompleted as i32);
-        if all_complete {
-            task.status = TaskStatus::TaskCompleted as i32;
-            task.completed_at = Self::now_ns();
-            info!(task_id = %task_id, "Task completed");
+
+        let snapshot = {
+            let mut task = self.tasks
+                .get_mut(task_id)
+                .ok_or_else(|| OrchestrationError::TaskNotFound(task_id.into()))?;
+
+            if let Some(error) = failure {
+                task.status = TaskStatus::TaskFailed as i32;
+                task.completed_at = Self::now_ns();
+                task.error = error;
+                warn!(task_id = %task_id, "Task failed");
+            } else if task.steps.iter().all(|s| s.status == TaskStatus::TaskCompleted as i32) {
+                task.status = TaskStatus::TaskCompleted as i32;
+                task.completed_at = Self::now_ns();
+                info!(task_id = %task_id, "Task completed");
+            }
+
+            task.clone()
+        };
+
+        self.storage.write_task_state(task_id, &snapshot).await?;
+        // As in `begin_task`, the steps above have already executed and the
+        // local mutation is already durable, so a replication shortfall must
+        // not discard the `executed` step list or mislead the caller into
+        // thinking none of this happened.
+        if let Err(err) = self.replication.replicate_task_state(task_id, &snapshot).await {
+            warn!(task_id = %task_id, error = %err, "Failed to replicate task state to peers");
         }
-        
+
         Ok(executed)
     }
+
+    /// Route a single step's message, retrying on routing errors up to
+    /// `max_retries` times before giving up.
+    async fn dispatch_step(
+        router: &CostAwareRouter,
+        message: &AiMessage,
+        max_retries: u32,
+    ) -> Result<RoutingDecision, OrchestrationError> {
+        let mut attempt = 0;
+        loop {
+            match router.route(message).await {
+                Ok(decision) => {
+                    router.record_endpoint_success(
+                        &decision.target_endpoint,
+                        decision.estimated_latency_ms as f64,
+                        decision.estimated_cost,
+                    );
+                    return Ok(decision);
+                }
+                Err(err) => {
+                    if attempt >= max_retries {
+                        return Err(OrchestrationError::TaskFailed(err.to_string()));
+                    }
+                    attempt += 1;
+                    warn!(attempt, error = %err, "Step routing failed, retrying");
+                }
+            }
+        }
+    }
     
     /// Wait for task completion with timeout
     pub async fn wait_for_completion(
@@ -173,14 +442,35 @@ impl OrchestrationEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::federation::FederationConfig;
+    use crate::protocol::{EndpointMetrics, HealthStatus};
     use crate::routing::RouterConfig;
     use crate::storage::StorageConfig;
-    
-    #[tokio::test]
-    async fn test_simple_task() {
+
+    fn test_endpoint(id: &str) -> EndpointMetrics {
+        EndpointMetrics {
+            endpoint_id: id.to_string(),
+            capacity: 100,
+            current_load: 0,
+            cost_per_1k_tokens: 1.0,
+            latency_p99_ms: 5.0,
+            error_rate: 0.0,
+            last_health_check: 0,
+            health_status: HealthStatus::Healthy as i32,
+        }
+    }
+
+    fn test_engine() -> OrchestrationEngine {
         let storage = Arc::new(StorageLayer::new(StorageConfig::default()).unwrap());
         let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
-        let engine = OrchestrationEngine::new(storage, router);
+        router.register_endpoint(test_endpoint("endpoint-1"));
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        OrchestrationEngine::new(storage, router, federation, OrchestrationConfig::default(), ReplicationConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_simple_task() {
+        let engine = test_engine();
         
         let msg = AiMessage::new("test-agent".into(), b"test".to_vec(), 100.0, i64::MAX);
         
@@ -215,4 +505,153 @@ mod tests {
         let task = engine.get_task(&task_id).unwrap();
         assert_eq!(task.status, TaskStatus::TaskCompleted as i32);
     }
+
+    #[tokio::test]
+    async fn test_begin_task_bounced_when_region_downgrading() {
+        let storage = Arc::new(StorageLayer::new(StorageConfig::default()).unwrap());
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let federation_config = FederationConfig::default();
+        let region_id = federation_config.region.id.clone();
+        let federation = Arc::new(FederationManager::new(federation_config));
+        federation.set_region_role_state_gracefully(&region_id, crate::federation::RegionRoleState::Leader).unwrap();
+        federation.set_region_role_state_gracefully(&region_id, crate::federation::RegionRoleState::Downgrading).unwrap();
+
+        let engine = OrchestrationEngine::new(storage, router, federation, OrchestrationConfig::default(), ReplicationConfig::default());
+
+        let mut msg = AiMessage::new("test-agent".into(), b"test".to_vec(), 100.0, i64::MAX);
+        msg.metadata.insert("target_region".into(), region_id.clone());
+
+        let graph = TaskGraph {
+            task_id: "task-downgrading".into(),
+            steps: vec![TaskStepDef {
+                step_id: "step-1".into(),
+                message: msg,
+                dependencies: vec![],
+            }],
+        };
+
+        let result = engine.begin_task(graph).await;
+        assert!(matches!(result, Err(OrchestrationError::RegionDowngrading(r)) if r == region_id));
+    }
+
+    #[tokio::test]
+    async fn test_begin_task_rejects_cyclic_dependencies() {
+        let engine = test_engine();
+        let msg = AiMessage::new("test-agent".into(), b"test".to_vec(), 100.0, i64::MAX);
+
+        let graph = TaskGraph {
+            task_id: "task-cycle".into(),
+            steps: vec![
+                TaskStepDef {
+                    step_id: "step-1".into(),
+                    message: msg.clone(),
+                    dependencies: vec!["step-2".into()],
+                },
+                TaskStepDef {
+                    step_id: "step-2".into(),
+                    message: msg,
+                    dependencies: vec!["step-1".into()],
+                },
+            ],
+        };
+
+        let result = engine.begin_task(graph).await;
+        assert!(matches!(result, Err(OrchestrationError::CyclicDependency(_))));
+    }
+
+    #[tokio::test]
+    async fn test_begin_task_rejects_unknown_dependency() {
+        let engine = test_engine();
+        let msg = AiMessage::new("test-agent".into(), b"test".to_vec(), 100.0, i64::MAX);
+
+        let graph = TaskGraph {
+            task_id: "task-dangling-dep".into(),
+            steps: vec![TaskStepDef {
+                step_id: "step-1".into(),
+                message: msg,
+                dependencies: vec!["step-does-not-exist".into()],
+            }],
+        };
+
+        let result = engine.begin_task(graph).await;
+        assert!(matches!(
+            result,
+            Err(OrchestrationError::UnknownDependency { step_id, dependency })
+                if step_id == "step-1" && dependency == "step-does-not-exist"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_begin_task_succeeds_despite_replication_quorum_shortfall() {
+        let storage = Arc::new(StorageLayer::new(StorageConfig::default()).unwrap());
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        router.register_endpoint(test_endpoint("endpoint-1"));
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        // Not enough peers registered anywhere to satisfy a 2-peer quorum.
+        let replication_config = ReplicationConfig {
+            mode: ReplicationMode::Quorum,
+            factor: 3,
+            write_quorum: 2,
+        };
+        let engine = OrchestrationEngine::new(
+            storage,
+            router,
+            federation,
+            OrchestrationConfig::default(),
+            replication_config,
+        );
+
+        let msg = AiMessage::new("test-agent".into(), b"test".to_vec(), 100.0, i64::MAX);
+        let graph = TaskGraph {
+            task_id: "task-under-replicated".into(),
+            steps: vec![TaskStepDef {
+                step_id: "step-1".into(),
+                message: msg,
+                dependencies: vec![],
+            }],
+        };
+
+        // A replication quorum failure must not be surfaced as an error:
+        // the task is already admitted and durable locally.
+        let task_id = engine.begin_task(graph).await.unwrap();
+        assert_eq!(task_id, "task-under-replicated");
+        assert!(engine.get_task(&task_id).is_some());
+
+        let executed = engine.execute_next(&task_id).await.unwrap();
+        assert_eq!(executed, vec!["step-1"]);
+        let task = engine.get_task(&task_id).unwrap();
+        assert_eq!(task.status, TaskStatus::TaskCompleted as i32);
+    }
+
+    #[tokio::test]
+    async fn test_execute_next_fails_task_when_no_healthy_endpoints() {
+        let storage = Arc::new(StorageLayer::new(StorageConfig::default()).unwrap());
+        let router = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        let engine = OrchestrationEngine::new(
+            storage,
+            router,
+            federation,
+            OrchestrationConfig { max_step_retries: 1 },
+            ReplicationConfig::default(),
+        );
+
+        let msg = AiMessage::new("test-agent".into(), b"test".to_vec(), 100.0, i64::MAX);
+        let graph = TaskGraph {
+            task_id: "task-no-endpoints".into(),
+            steps: vec![TaskStepDef {
+                step_id: "step-1".into(),
+                message: msg,
+                dependencies: vec![],
+            }],
+        };
+
+        engine.begin_task(graph).await.unwrap();
+        let executed = engine.execute_next("task-no-endpoints").await.unwrap();
+        assert!(executed.is_empty());
+
+        let task = engine.get_task("task-no-endpoints").unwrap();
+        assert_eq!(task.status, TaskStatus::TaskFailed as i32);
+        assert_eq!(task.steps[0].status, TaskStatus::TaskFailed as i32);
+    }
 }
@@ -0,0 +1,308 @@
+//! Cross-region replication for orchestration task state.
+//!
+//! `OrchestrationEngine` used to persist `TaskState` only to the local
+//! `StorageLayer`, so losing a region silently dropped every in-flight task.
+//! This module fans each write out to peers in other regions, chosen via
+//! `FederationManager::get_best_peer`, so a region promoted after a failover
+//! can reload outstanding `TaskState`s from its replicas and resume
+//! `execute_next`.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use thiserror::Error;
+use tokio::task::JoinSet;
+use tracing::{debug, warn};
+
+use crate::federation::{FederationManager, Peer};
+use crate::protocol::TaskState;
+
+#[derive(Error, Debug)]
+pub enum ReplicationError {
+    #[error("replica write quorum not reached: needed {needed}, acked {acked}")]
+    QuorumNotReached { needed: usize, acked: usize },
+    #[error("replica transport error: {0}")]
+    Transport(String),
+}
+
+/// How `ReplicationManager` fans a `TaskState` write out to peers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplicationMode {
+    /// Mirror every write to one peer in every other known region.
+    FullCopy,
+    /// Write to the `factor` best-scored peers across regions and consider
+    /// the write durable once `write_quorum` of them have acked.
+    Quorum,
+}
+
+/// Configuration for `ReplicationManager`.
+#[derive(Debug, Clone)]
+pub struct ReplicationConfig {
+    /// Replication strategy.
+    pub mode: ReplicationMode,
+    /// Number of replicas to target in `Quorum` mode. Ignored by `FullCopy`,
+    /// which always targets every other region.
+    pub factor: usize,
+    /// Number of acks required before a `Quorum` write is considered durable.
+    pub write_quorum: usize,
+}
+
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        Self {
+            mode: ReplicationMode::FullCopy,
+            factor: 3,
+            write_quorum: 2,
+        }
+    }
+}
+
+/// A pluggable carrier for shipping `TaskState` to a peer and reading it
+/// back, mirroring the role `PeerProber` plays for heartbeats.
+/// `InMemoryReplicaTransport` stands in until the QUIC transport layer
+/// carries these calls over the wire.
+#[async_trait]
+pub trait ReplicaTransport: Send + Sync {
+    /// Ship `state` to `peer`, overwriting any copy already held for `task_id`.
+    async fn put(&self, peer: &Peer, task_id: &str, state: &TaskState) -> Result<(), ReplicationError>;
+
+    /// List every `(task_id, TaskState)` `peer` is holding on our behalf.
+    async fn list(&self, peer: &Peer) -> Result<Vec<(String, TaskState)>, ReplicationError>;
+}
+
+/// In-process stand-in for a replica transport: one `TaskState` table per
+/// peer id, so tests and the default engine wiring can exercise replication
+/// and recovery without a real network hop.
+#[derive(Default)]
+pub struct InMemoryReplicaTransport {
+    store: DashMap<String, DashMap<String, TaskState>>,
+}
+
+#[async_trait]
+impl ReplicaTransport for InMemoryReplicaTransport {
+    async fn put(&self, peer: &Peer, task_id: &str, state: &TaskState) -> Result<(), ReplicationError> {
+        self.store
+            .entry(peer.id.clone())
+            .or_default()
+            .insert(task_id.to_string(), state.clone());
+        Ok(())
+    }
+
+    async fn list(&self, peer: &Peer) -> Result<Vec<(String, TaskState)>, ReplicationError> {
+        Ok(self
+            .store
+            .get(&peer.id)
+            .map(|table| table.iter().map(|e| (e.key().clone(), e.value().clone())).collect())
+            .unwrap_or_default())
+    }
+}
+
+/// Fans `TaskState` writes out across the federation and lets a newly
+/// promoted leader reload outstanding tasks from its replicas.
+pub struct ReplicationManager {
+    federation: Arc<FederationManager>,
+    config: ReplicationConfig,
+    transport: Arc<dyn ReplicaTransport>,
+}
+
+impl ReplicationManager {
+    /// Build a manager backed by the default in-memory transport.
+    pub fn new(federation: Arc<FederationManager>, config: ReplicationConfig) -> Self {
+        Self::with_transport(federation, config, Arc::new(InMemoryReplicaTransport::default()))
+    }
+
+    /// Build a manager backed by a custom transport, e.g. for tests that
+    /// need to inject partial failures.
+    pub fn with_transport(
+        federation: Arc<FederationManager>,
+        config: ReplicationConfig,
+        transport: Arc<dyn ReplicaTransport>,
+    ) -> Self {
+        Self {
+            federation,
+            config,
+            transport,
+        }
+    }
+
+    /// Mirrors `FederationManager::get_best_peer`'s scoring so replicas can
+    /// be ranked across regions for `Quorum` mode.
+    fn score(peer: &Peer) -> f64 {
+        peer.latency_ms as f64 * 0.5 + peer.load_percentage() * 100.0 * 0.5
+    }
+
+    /// One best peer per other known region, narrowed to `config.factor`
+    /// best-scored peers when `mode` is `Quorum`.
+    fn candidate_peers(&self) -> Vec<Peer> {
+        let local_region = self.federation.get_stats().local_region;
+
+        let mut peers: Vec<Peer> = self
+            .federation
+            .list_regions()
+            .into_iter()
+            .filter(|region| region.id != local_region)
+            .filter_map(|region| self.federation.get_best_peer(&region.id))
+            .collect();
+
+        if self.config.mode == ReplicationMode::Quorum {
+            peers.sort_by(|a, b| Self::score(a).partial_cmp(&Self::score(b)).unwrap_or(Ordering::Equal));
+            peers.truncate(self.config.factor);
+        }
+
+        peers
+    }
+
+    /// Replicate `state` to the configured peers, returning the number of
+    /// acks received. `FullCopy` returns however many of the (at most one
+    /// per region) targets acked; `Quorum` errors if fewer than
+    /// `write_quorum` peers acked.
+    pub async fn replicate_task_state(
+        &self,
+        task_id: &str,
+        state: &TaskState,
+    ) -> Result<usize, ReplicationError> {
+        let peers = self.candidate_peers();
+        if peers.is_empty() {
+            debug!(task_id = %task_id, "no remote peers available for replication");
+        }
+
+        let mut join_set = JoinSet::new();
+        for peer in peers {
+            let transport = Arc::clone(&self.transport);
+            let task_id = task_id.to_string();
+            let state = state.clone();
+            join_set.spawn(async move { transport.put(&peer, &task_id, &state).await });
+        }
+
+        let mut acked = 0usize;
+        while let Some(joined) = join_set.join_next().await {
+            match joined {
+                Ok(Ok(())) => acked += 1,
+                Ok(Err(err)) => warn!(task_id = %task_id, error = %err, "replica write failed"),
+                Err(err) => warn!(task_id = %task_id, error = %err, "replica write task panicked"),
+            }
+        }
+
+        match self.config.mode {
+            ReplicationMode::FullCopy => Ok(acked),
+            ReplicationMode::Quorum => {
+                if acked >= self.config.write_quorum {
+                    Ok(acked)
+                } else {
+                    Err(ReplicationError::QuorumNotReached {
+                        needed: self.config.write_quorum,
+                        acked,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Pull every `TaskState` replicated to a currently-healthy peer,
+    /// de-duplicating on `task_id` (first copy seen wins). Called by a
+    /// region after it is promoted to `Leader` so it can resume
+    /// `execute_next` on tasks it never ran locally.
+    pub async fn reload_outstanding_tasks(&self) -> HashMap<String, TaskState> {
+        let mut recovered = HashMap::new();
+
+        for peer in self.federation.get_healthy_peers() {
+            match self.transport.list(&peer).await {
+                Ok(entries) => {
+                    for (task_id, state) in entries {
+                        recovered.entry(task_id).or_insert(state);
+                    }
+                }
+                Err(err) => warn!(peer = %peer.id, error = %err, "failed to list replicated task state"),
+            }
+        }
+
+        recovered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::federation::{FederationConfig, GeoLocation, Peer, PeerStatus, Region};
+
+    fn region(id: &str) -> Region {
+        Region {
+            id: id.into(),
+            name: id.into(),
+            location: GeoLocation::new(0.0, 0.0, "US", id),
+        }
+    }
+
+    fn peer(id: &str, region_id: &str, latency_ms: u32) -> Peer {
+        let mut peer = Peer::new(id.into(), format!("{id}.local:9000"), region(region_id));
+        peer.status = PeerStatus::Healthy;
+        peer.latency_ms = latency_ms;
+        peer
+    }
+
+    fn sample_state(task_id: &str) -> TaskState {
+        TaskState {
+            task_id: task_id.into(),
+            status: 0,
+            steps: vec![],
+            started_at: 0,
+            completed_at: 0,
+            results: HashMap::new(),
+            error: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_full_copy_replicates_to_every_other_region() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        federation.register_peer(peer("peer-eu", "eu-west-1", 50));
+        federation.register_peer(peer("peer-ap", "ap-south-1", 80));
+
+        let manager = ReplicationManager::new(federation, ReplicationConfig::default());
+        let acked = manager
+            .replicate_task_state("task-1", &sample_state("task-1"))
+            .await
+            .unwrap();
+
+        assert_eq!(acked, 2);
+    }
+
+    #[tokio::test]
+    async fn test_quorum_errors_when_not_enough_peers_ack() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        federation.register_peer(peer("peer-eu", "eu-west-1", 50));
+
+        let manager = ReplicationManager::new(
+            federation,
+            ReplicationConfig {
+                mode: ReplicationMode::Quorum,
+                factor: 3,
+                write_quorum: 2,
+            },
+        );
+
+        let result = manager.replicate_task_state("task-1", &sample_state("task-1")).await;
+        assert!(matches!(
+            result,
+            Err(ReplicationError::QuorumNotReached { needed: 2, acked: 1 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reload_outstanding_tasks_recovers_replicated_state() {
+        let federation = Arc::new(FederationManager::new(FederationConfig::default()));
+        federation.register_peer(peer("peer-eu", "eu-west-1", 50));
+
+        let manager = ReplicationManager::new(federation, ReplicationConfig::default());
+        manager
+            .replicate_task_state("task-1", &sample_state("task-1"))
+            .await
+            .unwrap();
+
+        let recovered = manager.reload_outstanding_tasks().await;
+        assert!(recovered.contains_key("task-1"));
+    }
+}
@@ -4,7 +4,9 @@
 
 use std::collections::BinaryHeap;
 use std::cmp::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 use std::sync::Arc;
+use std::time::Instant;
 use parking_lot::Mutex;
 use crossbeam_channel::{Sender, Receiver, unbounded};
 use tracing::{debug, warn};
@@ -130,6 +132,14 @@ pub struct PriorityQueue {
     notify_rx: Receiver<()>,
     /// Configuration
     config: PriorityQueueConfig,
+    /// Set when the queue length crosses `high_watermark`, cleared once it
+    /// drops to `low_watermark` or below. Hysteresis between the two
+    /// watermarks keeps a queue hovering near the threshold from flapping
+    /// producers between `Accepted` and `Backpressure` on every push/pop.
+    in_backpressure: AtomicBool,
+    /// Woken on every successful `pop`, so `push_await`/`try_push_with_deadline`
+    /// can park instead of busy-polling for freed capacity.
+    capacity_notify: tokio::sync::Notify,
 }
 
 /// Priority queue configuration
@@ -141,18 +151,36 @@ pub struct PriorityQueueConfig {
     pub deadline_aware: bool,
     /// Drop expired messages
     pub drop_expired: bool,
+    /// Queue length at/above which `push` starts reporting
+    /// [`PushStatus::Backpressure`] so producers can slow down.
+    pub high_watermark: usize,
+    /// Queue length at/below which backpressure is released.
+    pub low_watermark: usize,
 }
 
 impl Default for PriorityQueueConfig {
     fn default() -> Self {
+        let max_size = 100_000;
         Self {
-            max_size: 100_000,
+            max_size,
             deadline_aware: true,
             drop_expired: true,
+            high_watermark: max_size * 8 / 10,
+            low_watermark: max_size / 2,
         }
     }
 }
 
+/// Outcome of a successful `push`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushStatus {
+    /// Accepted below the high watermark; producers may continue at full rate.
+    Accepted,
+    /// Accepted, but the queue is at/above `high_watermark` — producers
+    /// should slow down until a push reports `Accepted` again.
+    Backpressure,
+}
+
 impl PriorityQueue {
     pub fn new(config: PriorityQueueConfig) -> Self {
         let (notify_tx, notify_rx) = unbounded();
@@ -162,30 +190,96 @@ impl PriorityQueue {
             notify_tx,
             notify_rx,
             config,
+            in_backpressure: AtomicBool::new(false),
+            capacity_notify: tokio::sync::Notify::new(),
         }
     }
-    
+
     /// Enqueue a message
-    pub fn push(&self, message: AiMessage) -> Result<(), QueueError> {
+    pub fn push(&self, message: AiMessage) -> Result<PushStatus, QueueError> {
         let mut heap = self.heap.lock();
-        
+
         if heap.len() >= self.config.max_size {
             return Err(QueueError::Full);
         }
-        
+
         let prioritized = PrioritizedMessage::new(message);
         debug!(
             message_id = %prioritized.message.message_id,
             priority = ?prioritized.priority_level,
             "Enqueued message"
         );
-        
+
         heap.push(prioritized);
+        let len = heap.len();
+        drop(heap);
         let _ = self.notify_tx.send(());
-        
-        Ok(())
+
+        if len >= self.config.high_watermark {
+            self.in_backpressure.store(true, AtomicOrdering::Relaxed);
+        }
+
+        if self.in_backpressure.load(AtomicOrdering::Relaxed) {
+            Ok(PushStatus::Backpressure)
+        } else {
+            Ok(PushStatus::Accepted)
+        }
     }
-    
+
+    /// Enqueue a message, parking until the consumer frees capacity instead
+    /// of failing with [`QueueError::Full`]. Intended for producers that can
+    /// tolerate backpressure (unlike `push`'s immediate hard rejection).
+    pub async fn push_await(&self, message: AiMessage) -> Result<PushStatus, QueueError> {
+        loop {
+            // Register as a waiter *before* attempting the push, not after
+            // it fails: `pop()` wakes via `notify_waiters()`, which only
+            // wakes listeners already registered at the time it's called.
+            // Attempting the push first leaves a window where a `pop()`
+            // between the failed attempt and `.notified()` being created
+            // wakes nobody, and this waiter would then park until the next
+            // unrelated pop (or forever).
+            let notified = self.capacity_notify.notified();
+            match self.push(message.clone()) {
+                Ok(status) => return Ok(status),
+                Err(QueueError::Full) => notified.await,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Enqueue a message, waiting for freed capacity up to `deadline` before
+    /// giving up. Use the message's own `deadline_ms` (converted to an
+    /// `Instant`) so a producer never waits past the point the message would
+    /// expire anyway.
+    pub async fn try_push_with_deadline(
+        &self,
+        message: AiMessage,
+        deadline: Instant,
+    ) -> Result<PushStatus, QueueError> {
+        loop {
+            // See `push_await`: register before attempting the push so a
+            // `pop()` landing in the gap can't leave this waiter stuck past
+            // its own `deadline`-tracked timer.
+            let notified = self.capacity_notify.notified();
+            match self.push(message.clone()) {
+                Ok(status) => return Ok(status),
+                Err(QueueError::Full) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(QueueError::DeadlineExceeded);
+                    }
+                    tokio::select! {
+                        _ = notified => {}
+                        _ = tokio::time::sleep(deadline - now) => {
+                            return Err(QueueError::DeadlineExceeded);
+                        }
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
     /// Dequeue the highest priority message
     pub fn pop(&self) -> Option<PrioritizedMessage> {
         let mut heap = self.heap.lock();
@@ -206,10 +300,21 @@ impl PriorityQueue {
                 }
             }
         }
-        
-        heap.pop()
+
+        let popped = heap.pop();
+        let len = heap.len();
+        drop(heap);
+
+        if popped.is_some() {
+            if len <= self.config.low_watermark {
+                self.in_backpressure.store(false, AtomicOrdering::Relaxed);
+            }
+            self.capacity_notify.notify_waiters();
+        }
+
+        popped
     }
-    
+
     /// Peek at the highest priority message without removing
     pub fn peek(&self) -> Option<PrioritizedMessage> {
         self.heap.lock().peek().cloned()
@@ -276,6 +381,8 @@ pub enum QueueError {
     Full,
     #[error("Queue is closed")]
     Closed,
+    #[error("Deadline exceeded while waiting for queue capacity")]
+    DeadlineExceeded,
 }
 
 /// Queue statistics
@@ -292,7 +399,9 @@ pub struct QueueStats {
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use std::time::Duration;
+
+
     #[test]
     fn test_priority_ordering() {
         let queue = PriorityQueue::new(PriorityQueueConfig::default());
@@ -350,4 +459,74 @@ mod tests {
         let stats = queue.stats();
         assert_eq!(stats.total, 10);
     }
+
+    #[test]
+    fn test_push_reports_backpressure_with_hysteresis() {
+        let queue = PriorityQueue::new(PriorityQueueConfig {
+            max_size: 10,
+            high_watermark: 5,
+            low_watermark: 2,
+            ..PriorityQueueConfig::default()
+        });
+
+        let msg = || AiMessage::new("agent".into(), vec![], 100.0, i64::MAX);
+
+        for _ in 0..4 {
+            assert_eq!(queue.push(msg()).unwrap(), PushStatus::Accepted);
+        }
+        // Fifth push crosses the high watermark.
+        assert_eq!(queue.push(msg()).unwrap(), PushStatus::Backpressure);
+        // Still above the low watermark, so backpressure stays signaled.
+        assert_eq!(queue.push(msg()).unwrap(), PushStatus::Backpressure);
+
+        for _ in 0..4 {
+            queue.pop().unwrap();
+        }
+        // Draining down to the low watermark releases backpressure.
+        assert_eq!(queue.push(msg()).unwrap(), PushStatus::Accepted);
+    }
+
+    #[tokio::test]
+    async fn test_push_await_parks_until_capacity_frees() {
+        let queue = Arc::new(PriorityQueue::new(PriorityQueueConfig {
+            max_size: 1,
+            high_watermark: 1,
+            low_watermark: 0,
+            ..PriorityQueueConfig::default()
+        }));
+
+        queue.push(AiMessage::new("agent".into(), vec![], 100.0, i64::MAX)).unwrap();
+
+        let waiter = {
+            let queue = Arc::clone(&queue);
+            tokio::spawn(async move {
+                queue.push_await(AiMessage::new("agent".into(), vec![], 100.0, i64::MAX)).await
+            })
+        };
+
+        // Give the spawned task a chance to block on a full queue.
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        queue.pop().unwrap();
+        let status = waiter.await.unwrap().unwrap();
+        // The parked push lands at length 1 == high_watermark.
+        assert_eq!(status, PushStatus::Backpressure);
+    }
+
+    #[tokio::test]
+    async fn test_try_push_with_deadline_times_out_on_full_queue() {
+        let queue = PriorityQueue::new(PriorityQueueConfig {
+            max_size: 1,
+            ..PriorityQueueConfig::default()
+        });
+        queue.push(AiMessage::new("agent".into(), vec![], 100.0, i64::MAX)).unwrap();
+
+        let deadline = Instant::now() + Duration::from_millis(20);
+        let result = queue
+            .try_push_with_deadline(AiMessage::new("agent".into(), vec![], 100.0, i64::MAX), deadline)
+            .await;
+
+        assert!(matches!(result, Err(QueueError::DeadlineExceeded)));
+    }
 }
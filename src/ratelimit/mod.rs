@@ -3,11 +3,14 @@
 //! Token bucket and sliding window rate limiters for fair resource allocation.
 
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use dashmap::DashMap;
 use thiserror::Error;
 use tracing::{debug, warn};
 
+use crate::protocol::AiMessage;
+
 #[derive(Error, Debug)]
 pub enum RateLimitError {
     #[error("Rate limit exceeded for {key}: {limit} requests per {window_secs}s")]
@@ -16,8 +19,150 @@ pub enum RateLimitError {
         limit: u64,
         window_secs: u64,
     },
+    #[error("Bandwidth limit exceeded for {key}: {limit} bytes per second")]
+    BandwidthExceeded { key: String, limit: u64 },
     #[error("Quota exhausted for {key}")]
     QuotaExhausted { key: String },
+
+    #[error("Rule '{rule}' exceeded for {key}: {limit} per {window_secs}s")]
+    RuleLimitExceeded {
+        rule: String,
+        key: String,
+        limit: u64,
+        window_secs: u64,
+    },
+}
+
+/// Which dimension a token bucket governs.
+///
+/// Operations and bandwidth are tracked by independent buckets so a
+/// handful of oversized payloads can't starve a key's request slots
+/// (and vice versa).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenType {
+    /// Request/operation slots.
+    Ops,
+    /// Payload bytes.
+    Bytes,
+}
+
+/// An `AiMessage` field a [`RuleCondition`] or rule [`variables`](LimitRule::variables)
+/// list can reference.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MessageField {
+    /// `AiMessage::agent_id`
+    AgentId,
+    /// `AiMessage::priority` (0-100), compared/extracted as its decimal string.
+    Priority,
+    /// `AiMessage::task_graph_id`
+    TaskGraphId,
+    /// A value looked up in `AiMessage::metadata` by key, e.g. a
+    /// caller-attached `tier` or `message_type` tag. Missing keys extract as
+    /// an empty string.
+    Metadata(String),
+}
+
+impl MessageField {
+    /// Extract this field's value from `message` as a string, for use as a
+    /// condition operand or as a component of a derived bucket key.
+    fn extract(&self, message: &AiMessage) -> String {
+        match self {
+            MessageField::AgentId => message.agent_id.clone(),
+            MessageField::Priority => message.priority.to_string(),
+            MessageField::TaskGraphId => message.task_graph_id.clone(),
+            MessageField::Metadata(key) => message.metadata.get(key).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+/// Comparison predicate for a [`RuleCondition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+/// A single predicate over one [`MessageField`] of an `AiMessage`.
+///
+/// `Eq`/`Ne` compare the field's extracted string value directly.
+/// `Gt`/`Gte`/`Lt`/`Lte` parse both the extracted value and `value` as
+/// `f64` and compare numerically; a condition that fails to parse never
+/// matches.
+#[derive(Debug, Clone)]
+pub struct RuleCondition {
+    pub field: MessageField,
+    pub op: ComparisonOp,
+    pub value: String,
+}
+
+impl RuleCondition {
+    pub fn new(field: MessageField, op: ComparisonOp, value: impl Into<String>) -> Self {
+        Self { field, op, value: value.into() }
+    }
+
+    fn matches(&self, message: &AiMessage) -> bool {
+        let actual = self.field.extract(message);
+        match self.op {
+            ComparisonOp::Eq => actual == self.value,
+            ComparisonOp::Ne => actual != self.value,
+            ComparisonOp::Gt | ComparisonOp::Gte | ComparisonOp::Lt | ComparisonOp::Lte => {
+                match (actual.parse::<f64>(), self.value.parse::<f64>()) {
+                    (Ok(a), Ok(b)) => match self.op {
+                        ComparisonOp::Gt => a > b,
+                        ComparisonOp::Gte => a >= b,
+                        ComparisonOp::Lt => a < b,
+                        ComparisonOp::Lte => a <= b,
+                        ComparisonOp::Eq | ComparisonOp::Ne => unreachable!(),
+                    },
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+/// A declarative conditional rate limit: "when `conditions` all match,
+/// bucket the message by `variables` and allow at most `limit` per
+/// `window_secs`".
+///
+/// For example, `{ conditions: [priority <= 10], variables: [agent_id],
+/// limit: 10, window_secs: 60 }` expresses "low-priority messages: 10/min
+/// per agent" without the caller precomputing a composite key.
+#[derive(Debug, Clone)]
+pub struct LimitRule {
+    /// Identifies this rule in [`RateLimitError::RuleLimitExceeded`].
+    pub name: String,
+    /// The message must match every condition for this rule to apply.
+    pub conditions: Vec<RuleCondition>,
+    /// Fields whose extracted values are joined to derive the bucket key,
+    /// so e.g. `[AgentId]` buckets per-agent while `[]` buckets globally
+    /// across every message that matches `conditions`.
+    pub variables: Vec<MessageField>,
+    /// Maximum matching messages allowed within `window_secs`.
+    pub limit: u64,
+    pub window_secs: u64,
+}
+
+impl LimitRule {
+    fn matches(&self, message: &AiMessage) -> bool {
+        self.conditions.iter().all(|c| c.matches(message))
+    }
+
+    /// Derive this rule's bucket key for `message` by joining its
+    /// `variables` values, namespaced by rule name so identical variable
+    /// values under different rules never collide.
+    fn derive_key(&self, message: &AiMessage) -> String {
+        let mut key = self.name.clone();
+        for field in &self.variables {
+            key.push('\u{1}');
+            key.push_str(&field.extract(message));
+        }
+        key
+    }
 }
 
 /// Rate limiter configuration
@@ -27,30 +172,72 @@ pub struct RateLimitConfig {
     pub requests_per_second: u64,
     /// Burst capacity (token bucket size)
     pub burst_capacity: u64,
+    /// Bytes per second limit (bandwidth dimension)
+    pub bytes_per_second: u64,
+    /// Burst capacity for the bandwidth bucket, in bytes
+    pub byte_burst_capacity: u64,
     /// Sliding window duration in seconds
     pub window_secs: u64,
-    /// Enable adaptive rate limiting
+    /// Enable adaptive rate limiting: per-key ops/bandwidth refill rates are
+    /// retuned on each `start_adaptive_tuning` tick instead of staying fixed
+    /// at `requests_per_second`/`bytes_per_second`.
     pub adaptive: bool,
+    /// Floor for the adaptive ops refill rate (tokens/sec)
+    pub adaptive_ops_floor: u64,
+    /// Ceiling for the adaptive ops refill rate (tokens/sec)
+    pub adaptive_ops_ceiling: u64,
+    /// Floor for the adaptive bandwidth refill rate (bytes/sec)
+    pub adaptive_bytes_floor: u64,
+    /// Ceiling for the adaptive bandwidth refill rate (bytes/sec)
+    pub adaptive_bytes_ceiling: u64,
 }
 
 impl Default for RateLimitConfig {
     fn default() -> Self {
+        let requests_per_second = 100;
+        let bytes_per_second = 10 * 1024 * 1024; // 10 MB/s
         Self {
-            requests_per_second: 100,
+            requests_per_second,
             burst_capacity: 200,
+            bytes_per_second,
+            byte_burst_capacity: 20 * 1024 * 1024,
             window_secs: 60,
             adaptive: true,
+            adaptive_ops_floor: requests_per_second / 4,
+            adaptive_ops_ceiling: requests_per_second * 4,
+            adaptive_bytes_floor: bytes_per_second / 4,
+            adaptive_bytes_ceiling: bytes_per_second * 4,
         }
     }
 }
 
+/// Multiplicative increase factor applied to a key's adaptive refill rate
+/// when rejections are rare and the bucket is running hot.
+const ADAPTIVE_INCREASE_FACTOR: f64 = 1.1;
+/// Multiplicative decrease factor (halving) applied on rejection spikes or
+/// an explicit `report_backpressure` signal, AIMD-style.
+const ADAPTIVE_DECREASE_FACTOR: f64 = 0.5;
+/// Below this rejection ratio, the key is considered healthy.
+const ADAPTIVE_LOW_REJECTION_RATIO: f64 = 0.02;
+/// At or above this rejection ratio, the key is considered congested.
+const ADAPTIVE_HIGH_REJECTION_RATIO: f64 = 0.15;
+/// Bucket utilization (1 - available/capacity) above which a healthy key is
+/// considered to be running hot enough to justify raising its rate.
+const ADAPTIVE_HIGH_UTILIZATION: f64 = 0.8;
+
 /// Token bucket state
 #[derive(Debug)]
 struct TokenBucket {
     tokens: AtomicU64,
     last_refill: parking_lot::Mutex<Instant>,
     capacity: u64,
-    refill_rate: u64, // tokens per second
+    /// Tokens per second. An `AtomicU64` rather than a plain field because
+    /// the adaptive controller retunes it in place on each background tick.
+    refill_rate: AtomicU64,
+    /// Acquisitions granted since the last adaptive tick was taken.
+    accepted: AtomicU64,
+    /// Acquisitions denied since the last adaptive tick was taken.
+    rejected: AtomicU64,
 }
 
 impl TokenBucket {
@@ -59,37 +246,42 @@ impl TokenBucket {
             tokens: AtomicU64::new(capacity),
             last_refill: parking_lot::Mutex::new(Instant::now()),
             capacity,
-            refill_rate,
+            refill_rate: AtomicU64::new(refill_rate),
+            accepted: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
         }
     }
-    
+
     fn try_acquire(&self, count: u64) -> bool {
         self.refill();
-        
+
         loop {
             let current = self.tokens.load(Ordering::Relaxed);
             if current < count {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
                 return false;
             }
-            
+
             if self.tokens.compare_exchange(
                 current,
                 current - count,
                 Ordering::SeqCst,
                 Ordering::Relaxed,
             ).is_ok() {
+                self.accepted.fetch_add(1, Ordering::Relaxed);
                 return true;
             }
         }
     }
-    
+
     fn refill(&self) {
         let mut last = self.last_refill.lock();
         let now = Instant::now();
         let elapsed = now.duration_since(*last);
-        
+
         if elapsed.as_millis() > 0 {
-            let new_tokens = (elapsed.as_millis() as u64 * self.refill_rate) / 1000;
+            let rate = self.refill_rate.load(Ordering::Relaxed);
+            let new_tokens = (elapsed.as_millis() as u64 * rate) / 1000;
             if new_tokens > 0 {
                 let current = self.tokens.load(Ordering::Relaxed);
                 let new_value = (current + new_tokens).min(self.capacity);
@@ -98,11 +290,45 @@ impl TokenBucket {
             }
         }
     }
-    
+
     fn available(&self) -> u64 {
         self.refill();
         self.tokens.load(Ordering::Relaxed)
     }
+
+    fn current_rate(&self) -> u64 {
+        self.refill_rate.load(Ordering::Relaxed)
+    }
+
+    fn set_rate(&self, rate: u64) {
+        self.refill_rate.store(rate, Ordering::Relaxed);
+    }
+
+    /// Drain the accept/reject counters accumulated since the last tick,
+    /// returning `(accepted, rejected)`.
+    fn take_counts(&self) -> (u64, u64) {
+        (
+            self.accepted.swap(0, Ordering::Relaxed),
+            self.rejected.swap(0, Ordering::Relaxed),
+        )
+    }
+
+    /// Return previously-debited tokens, e.g. to undo a partial
+    /// multi-bucket acquisition. Never exceeds bucket capacity.
+    fn release(&self, count: u64) {
+        loop {
+            let current = self.tokens.load(Ordering::Relaxed);
+            let restored = (current + count).min(self.capacity);
+            if self.tokens.compare_exchange(
+                current,
+                restored,
+                Ordering::SeqCst,
+                Ordering::Relaxed,
+            ).is_ok() {
+                return;
+            }
+        }
+    }
 }
 
 /// Sliding window counter
@@ -157,12 +383,20 @@ impl SlidingWindow {
 /// Combined rate limiter with token bucket and sliding window
 pub struct RateLimiter {
     config: RateLimitConfig,
-    /// Per-key token buckets
+    /// Per-key operation token buckets
     buckets: DashMap<String, TokenBucket>,
+    /// Per-key bandwidth (bytes) token buckets
+    byte_buckets: DashMap<String, TokenBucket>,
     /// Per-key sliding windows
     windows: DashMap<String, SlidingWindow>,
     /// Global token bucket
     global_bucket: TokenBucket,
+    /// Global bandwidth bucket
+    global_byte_bucket: TokenBucket,
+    /// Declarative conditional limit rules, evaluated by [`Self::acquire_rules`].
+    rules: parking_lot::RwLock<Vec<LimitRule>>,
+    /// Per-derived-key sliding windows backing the rules above.
+    rule_windows: DashMap<String, SlidingWindow>,
 }
 
 impl RateLimiter {
@@ -171,15 +405,23 @@ impl RateLimiter {
             config.burst_capacity * 10, // 10x for global
             config.requests_per_second * 10,
         );
-        
+        let global_byte_bucket = TokenBucket::new(
+            config.byte_burst_capacity * 10,
+            config.bytes_per_second * 10,
+        );
+
         Self {
             config,
             buckets: DashMap::new(),
+            byte_buckets: DashMap::new(),
             windows: DashMap::new(),
             global_bucket,
+            global_byte_bucket,
+            rules: parking_lot::RwLock::new(Vec::new()),
+            rule_windows: DashMap::new(),
         }
     }
-    
+
     /// Check if request is allowed (does not consume)
     pub fn check(&self, key: &str) -> bool {
         // Check global limit
@@ -253,27 +495,152 @@ impl RateLimiter {
         
         Ok(())
     }
-    
+
+    /// Try to acquire `byte_count` bytes of bandwidth for `key`.
+    ///
+    /// Debits the global and per-key byte buckets only; it does not
+    /// touch the ops buckets or sliding window. Use [`Self::acquire_message`]
+    /// to debit both dimensions together.
+    pub fn acquire_bytes(&self, key: &str, byte_count: u64) -> Result<(), RateLimitError> {
+        if !self.global_byte_bucket.try_acquire(byte_count) {
+            warn!(key = %key, byte_count, "Global bandwidth limit hit");
+            return Err(RateLimitError::BandwidthExceeded {
+                key: "global".to_string(),
+                limit: self.config.bytes_per_second * 10,
+            });
+        }
+
+        let byte_bucket = self.byte_buckets.entry(key.to_string()).or_insert_with(|| {
+            TokenBucket::new(self.config.byte_burst_capacity, self.config.bytes_per_second)
+        });
+
+        if !byte_bucket.try_acquire(byte_count) {
+            self.global_byte_bucket.release(byte_count);
+            debug!(key = %key, byte_count, "Per-key bandwidth limit hit");
+            return Err(RateLimitError::BandwidthExceeded {
+                key: key.to_string(),
+                limit: self.config.bytes_per_second,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Acquire both the operation slot and the bandwidth for a message in
+    /// one call, following the [`TokenType::Ops`] and [`TokenType::Bytes`]
+    /// dimensions. Succeeds only if both buckets have capacity. When the
+    /// bandwidth side fails, the ops tokens already debited are returned to
+    /// the global and per-key buckets; the ops sliding-window count that
+    /// `acquire_n` recorded is kept, and a rejection inside `acquire_n`
+    /// itself does not return the global ops token it already took.
+    pub fn acquire_message(&self, key: &str, message: &AiMessage) -> Result<(), RateLimitError> {
+        self.acquire_n(key, 1)?;
+
+        let byte_count = message.payload.len() as u64;
+        if let Err(err) = self.acquire_bytes(key, byte_count) {
+            self.release_ops(key, 1);
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    /// Replace the active set of conditional limit rules, reconfigurable at
+    /// runtime. Clears per-rule windows so limits changed mid-flight take
+    /// effect immediately rather than inheriting a stale counter.
+    pub fn set_rules(&self, rules: Vec<LimitRule>) {
+        *self.rules.write() = rules;
+        self.rule_windows.clear();
+    }
+
+    /// Evaluate every conditional rule against `message`, deriving each
+    /// matching rule's bucket key from its `variables` instead of requiring
+    /// the caller to precompute one. Rejects on the first exhausted rule,
+    /// naming which one tripped; does not touch the ops/bandwidth buckets
+    /// used by [`Self::acquire_message`].
+    pub fn acquire_rules(&self, message: &AiMessage) -> Result<(), RateLimitError> {
+        let rules = self.rules.read();
+        for rule in rules.iter() {
+            if !rule.matches(message) {
+                continue;
+            }
+
+            let derived_key = rule.derive_key(message);
+            let window = self.rule_windows.entry(derived_key.clone()).or_insert_with(|| {
+                SlidingWindow::new(rule.window_secs, rule.limit)
+            });
+
+            if !window.try_acquire(1) {
+                debug!(rule = %rule.name, key = %derived_key, "Conditional rate limit rule hit");
+                return Err(RateLimitError::RuleLimitExceeded {
+                    rule: rule.name.clone(),
+                    key: derived_key,
+                    limit: rule.limit,
+                    window_secs: rule.window_secs,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Undo a previously-successful ops acquisition (used to roll back the
+    /// ops side of [`Self::acquire_message`] when the bandwidth side fails).
+    fn release_ops(&self, key: &str, count: u64) {
+        self.global_bucket.release(count);
+        if let Some(bucket) = self.buckets.get(key) {
+            bucket.release(count);
+        }
+    }
+
     /// Get current usage for a key
     pub fn get_usage(&self, key: &str) -> RateLimitUsage {
         let bucket_available = self.buckets.get(key)
             .map(|b| b.available())
             .unwrap_or(self.config.burst_capacity);
-        
+
+        let bytes_available = self.byte_buckets.get(key)
+            .map(|b| b.available())
+            .unwrap_or(self.config.byte_burst_capacity);
+
         let window_count = self.windows.get(key)
             .map(|w| w.current_count())
             .unwrap_or(0);
-        
+
+        let effective_ops_rate = self.buckets.get(key)
+            .map(|b| b.current_rate())
+            .unwrap_or(self.config.requests_per_second);
+
+        let effective_bytes_rate = self.byte_buckets.get(key)
+            .map(|b| b.current_rate())
+            .unwrap_or(self.config.bytes_per_second);
+
         RateLimitUsage {
             tokens_available: bucket_available,
+            bytes_available,
             window_count,
             window_limit: self.config.requests_per_second * self.config.window_secs,
+            effective_ops_rate,
+            effective_bytes_rate,
         }
     }
-    
+
+    /// Get current usage for a single dimension of a key.
+    pub fn get_usage_for(&self, key: &str, token_type: TokenType) -> u64 {
+        match token_type {
+            TokenType::Ops => self.buckets.get(key)
+                .map(|b| b.available())
+                .unwrap_or(self.config.burst_capacity),
+            TokenType::Bytes => self.byte_buckets.get(key)
+                .map(|b| b.available())
+                .unwrap_or(self.config.byte_burst_capacity),
+        }
+    }
+
     /// Reset rate limit for a key
     pub fn reset(&self, key: &str) {
         self.buckets.remove(key);
+        self.byte_buckets.remove(key);
         self.windows.remove(key);
     }
     
@@ -284,14 +651,98 @@ impl RateLimiter {
             .map(|entry| entry.key().clone())
             .collect()
     }
+
+    /// Report downstream backpressure for `key`, immediately halving its
+    /// current ops and bandwidth refill rates (AIMD multiplicative
+    /// decrease), down to the configured floors. No-op if `key` has not
+    /// acquired anything yet.
+    pub fn report_backpressure(&self, key: &str) {
+        if let Some(bucket) = self.buckets.get(key) {
+            let new_rate = ((bucket.current_rate() as f64 * ADAPTIVE_DECREASE_FACTOR) as u64)
+                .max(self.config.adaptive_ops_floor);
+            bucket.set_rate(new_rate);
+            warn!(key = %key, new_rate, "rate limiter: backpressure reported, halving ops rate");
+        }
+
+        if let Some(bucket) = self.byte_buckets.get(key) {
+            let new_rate = ((bucket.current_rate() as f64 * ADAPTIVE_DECREASE_FACTOR) as u64)
+                .max(self.config.adaptive_bytes_floor);
+            bucket.set_rate(new_rate);
+            warn!(key = %key, new_rate, "rate limiter: backpressure reported, halving bandwidth rate");
+        }
+    }
+
+    /// Run one adaptive-tuning pass over every per-key bucket: keys with few
+    /// rejections and a hot bucket get their refill rate multiplicatively
+    /// increased toward the ceiling, keys with a rejection spike get it
+    /// halved toward the floor. Keys with no activity since the last pass
+    /// are left untouched. No-op when `adaptive` is disabled.
+    fn tune_adaptive(&self) {
+        if !self.config.adaptive {
+            return;
+        }
+        Self::tune_bucket_map(&self.buckets, self.config.adaptive_ops_floor, self.config.adaptive_ops_ceiling);
+        Self::tune_bucket_map(&self.byte_buckets, self.config.adaptive_bytes_floor, self.config.adaptive_bytes_ceiling);
+    }
+
+    fn tune_bucket_map(buckets: &DashMap<String, TokenBucket>, floor: u64, ceiling: u64) {
+        for entry in buckets.iter() {
+            let bucket = entry.value();
+            let (accepted, rejected) = bucket.take_counts();
+            let total = accepted + rejected;
+            if total == 0 {
+                continue;
+            }
+
+            let rejection_ratio = rejected as f64 / total as f64;
+            let utilization = 1.0 - (bucket.available() as f64 / bucket.capacity.max(1) as f64);
+            let current = bucket.current_rate();
+
+            let new_rate = if rejection_ratio >= ADAPTIVE_HIGH_REJECTION_RATIO {
+                (current as f64 * ADAPTIVE_DECREASE_FACTOR) as u64
+            } else if rejection_ratio <= ADAPTIVE_LOW_REJECTION_RATIO && utilization >= ADAPTIVE_HIGH_UTILIZATION {
+                (current as f64 * ADAPTIVE_INCREASE_FACTOR) as u64
+            } else {
+                current
+            };
+
+            bucket.set_rate(new_rate.clamp(floor, ceiling));
+        }
+    }
+
+    /// Start a background task that retunes every per-key adaptive rate
+    /// every `interval`, following an AIMD controller: multiplicative
+    /// increase on a healthy, hot bucket; multiplicative decrease on a
+    /// rejection spike. A no-op tick if `adaptive` is disabled in the
+    /// config this limiter was built with.
+    pub fn start_adaptive_tuning(
+        self: &Arc<Self>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let limiter = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                limiter.tune_adaptive();
+            }
+        })
+    }
 }
 
 /// Rate limit usage information
 #[derive(Debug, Clone)]
 pub struct RateLimitUsage {
     pub tokens_available: u64,
+    pub bytes_available: u64,
     pub window_count: u64,
     pub window_limit: u64,
+    /// Current effective ops refill rate (tokens/sec), as retuned by the
+    /// adaptive controller if enabled.
+    pub effective_ops_rate: u64,
+    /// Current effective bandwidth refill rate (bytes/sec), as retuned by
+    /// the adaptive controller if enabled.
+    pub effective_bytes_rate: u64,
 }
 
 impl RateLimitUsage {
@@ -324,8 +775,11 @@ mod tests {
         let config = RateLimitConfig {
             requests_per_second: 10,
             burst_capacity: 20,
+            bytes_per_second: 1024,
+            byte_burst_capacity: 2048,
             window_secs: 1,
             adaptive: false,
+            ..RateLimitConfig::default()
         };
         
         let limiter = RateLimiter::new(config);
@@ -350,4 +804,162 @@ mod tests {
         let usage = limiter.get_usage("key1");
         assert_eq!(usage.window_count, 2);
     }
+
+    #[test]
+    fn test_acquire_bytes_respects_bandwidth_limit() {
+        let config = RateLimitConfig {
+            bytes_per_second: 1000,
+            byte_burst_capacity: 1000,
+            ..RateLimitConfig::default()
+        };
+        let limiter = RateLimiter::new(config);
+
+        assert!(limiter.acquire_bytes("key1", 600).is_ok());
+        assert!(limiter.acquire_bytes("key1", 500).is_err());
+        // Partial failure must not have consumed the 500 bytes.
+        assert_eq!(limiter.get_usage_for("key1", TokenType::Bytes), 400);
+    }
+
+    #[test]
+    fn test_acquire_message_debits_both_dimensions_atomically() {
+        let config = RateLimitConfig {
+            requests_per_second: 100,
+            burst_capacity: 100,
+            bytes_per_second: 1000,
+            byte_burst_capacity: 1000,
+            ..RateLimitConfig::default()
+        };
+        let limiter = RateLimiter::new(config);
+
+        // Drain the byte bucket so the bandwidth side is guaranteed to fail.
+        limiter.acquire_bytes("key1", 900).unwrap();
+
+        let ops_before = limiter.get_usage_for("key1", TokenType::Ops);
+        let message = AiMessage::new("key1".to_string(), vec![0u8; 200], 10.0, 1_000);
+        assert!(limiter.acquire_message("key1", &message).is_err());
+
+        // The ops bucket must be restored since bandwidth failed.
+        assert_eq!(limiter.get_usage_for("key1", TokenType::Ops), ops_before);
+    }
+
+    #[test]
+    fn test_adaptive_tuning_decreases_rate_on_rejection_spike() {
+        let config = RateLimitConfig {
+            requests_per_second: 10,
+            burst_capacity: 5,
+            adaptive: true,
+            adaptive_ops_floor: 1,
+            adaptive_ops_ceiling: 40,
+            ..RateLimitConfig::default()
+        };
+        let limiter = RateLimiter::new(config);
+
+        // Exhaust the burst, then keep failing to acquire so rejections
+        // dominate the accept/reject ratio.
+        for _ in 0..5 {
+            limiter.acquire("key1").unwrap();
+        }
+        for _ in 0..20 {
+            let _ = limiter.acquire("key1");
+        }
+
+        let rate_before = limiter.get_usage("key1").effective_ops_rate;
+        limiter.tune_adaptive();
+        let rate_after = limiter.get_usage("key1").effective_ops_rate;
+
+        assert!(rate_after < rate_before, "expected AIMD decrease, got {rate_before} -> {rate_after}");
+    }
+
+    #[test]
+    fn test_report_backpressure_halves_rate_down_to_floor() {
+        let config = RateLimitConfig {
+            adaptive_ops_floor: 20,
+            ..RateLimitConfig::default()
+        };
+        let limiter = RateLimiter::new(config);
+        limiter.acquire("key1").unwrap();
+
+        limiter.report_backpressure("key1");
+        limiter.report_backpressure("key1");
+        limiter.report_backpressure("key1");
+        limiter.report_backpressure("key1");
+        limiter.report_backpressure("key1");
+        limiter.report_backpressure("key1");
+
+        assert_eq!(limiter.get_usage("key1").effective_ops_rate, 20);
+    }
+
+    #[test]
+    fn test_rule_matches_only_when_all_conditions_hold() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        limiter.set_rules(vec![LimitRule {
+            name: "low-priority-per-agent".to_string(),
+            conditions: vec![RuleCondition::new(MessageField::Priority, ComparisonOp::Lte, "10")],
+            variables: vec![MessageField::AgentId],
+            limit: 2,
+            window_secs: 60,
+        }]);
+
+        let mut low = AiMessage::new("agent-a".to_string(), vec![], 10.0, 0);
+        low.priority = 5;
+        let mut high = AiMessage::new("agent-a".to_string(), vec![], 10.0, 0);
+        high.priority = 90;
+
+        // High-priority messages never match the rule, so they're unbounded.
+        for _ in 0..5 {
+            assert!(limiter.acquire_rules(&high).is_ok());
+        }
+
+        // Low-priority messages are capped at 2 per window for this agent.
+        assert!(limiter.acquire_rules(&low).is_ok());
+        assert!(limiter.acquire_rules(&low).is_ok());
+        let err = limiter.acquire_rules(&low).unwrap_err();
+        assert!(matches!(err, RateLimitError::RuleLimitExceeded { rule, .. } if rule == "low-priority-per-agent"));
+    }
+
+    #[test]
+    fn test_rule_keys_are_derived_per_variable_value() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        limiter.set_rules(vec![LimitRule {
+            name: "per-agent".to_string(),
+            conditions: vec![],
+            variables: vec![MessageField::AgentId],
+            limit: 1,
+            window_secs: 60,
+        }]);
+
+        let a = AiMessage::new("agent-a".to_string(), vec![], 10.0, 0);
+        let b = AiMessage::new("agent-b".to_string(), vec![], 10.0, 0);
+
+        assert!(limiter.acquire_rules(&a).is_ok());
+        // Different agent_id -> different derived key -> independent budget.
+        assert!(limiter.acquire_rules(&b).is_ok());
+        assert!(limiter.acquire_rules(&a).is_err());
+    }
+
+    #[test]
+    fn test_set_rules_resets_counters_for_new_config() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        limiter.set_rules(vec![LimitRule {
+            name: "r".to_string(),
+            conditions: vec![],
+            variables: vec![],
+            limit: 1,
+            window_secs: 60,
+        }]);
+
+        let msg = AiMessage::new("agent-a".to_string(), vec![], 10.0, 0);
+        assert!(limiter.acquire_rules(&msg).is_ok());
+        assert!(limiter.acquire_rules(&msg).is_err());
+
+        // Reconfiguring clears stale rule windows even if the rule name repeats.
+        limiter.set_rules(vec![LimitRule {
+            name: "r".to_string(),
+            conditions: vec![],
+            variables: vec![],
+            limit: 1,
+            window_secs: 60,
+        }]);
+        assert!(limiter.acquire_rules(&msg).is_ok());
+    }
 }
@@ -0,0 +1,176 @@
+//! Snapshot-certification layer for budget consumption, so the same agent
+//! budget can be drawn down from multiple `CostAwareRouter` instances
+//! without double-spending.
+//!
+//! `consume_budget` used to take a per-entry lock on `budgets: DashMap`,
+//! which only serializes writers within one process. Spread the same agent
+//! across routers on different nodes and each node's lock is independent,
+//! so two concurrent consumes can both observe `remaining_tokens` before
+//! either commits and drive the balance negative. Instead, every consume
+//! reads a `(version, remaining_tokens)` snapshot and submits a candidate
+//! `(read_version, delta)` to a [`BudgetCertifier`]: the certifier is the
+//! single place that decides commit-or-abort, so it can be backed by
+//! something that actually serializes across nodes (a consensus log, a
+//! single certifier service) instead of a per-process mutex.
+//!
+//! The read-snapshot / certify-or-abort protocol mirrors the Talos cohort
+//! certification model: a candidate always certifies against the latest
+//! committed state (not the possibly-stale snapshot it was computed from),
+//! so correctness never depends on the reader being up to date.
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use thiserror::Error;
+
+/// A certified point-in-time view of an agent's budget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetSnapshot {
+    pub version: u64,
+    pub remaining_tokens: f64,
+}
+
+/// Why a candidate failed to certify.
+#[derive(Error, Debug)]
+pub enum CertifyError {
+    #[error("insufficient remaining budget for {agent_id}: need {delta}, have {remaining}")]
+    InsufficientRemaining { agent_id: String, delta: f64, remaining: f64 },
+    #[error("candidate for {agent_id} conflicted with a concurrent commit, retry with a fresh snapshot")]
+    Conflict { agent_id: String },
+}
+
+/// Pluggable backend that certifies budget-consumption candidates.
+///
+/// The default [`InMemoryBudgetCertifier`] is the single-node fast path: a
+/// per-agent mutex makes every candidate certify against the true latest
+/// state, so it never needs to reject for staleness. A distributed backend
+/// (e.g. one backed by a real ordered commit log shared across nodes) can
+/// implement this trait and may legitimately return
+/// [`CertifyError::Conflict`] when a candidate races another cohort's
+/// commit; `CostAwareRouter::consume_budget` retries those with a fresh
+/// snapshot.
+pub trait BudgetCertifier: Send + Sync {
+    /// Read the current committed snapshot for `agent_id`, initializing it
+    /// to `default_remaining` if this is the first time the agent is seen.
+    fn read_snapshot(&self, agent_id: &str, default_remaining: f64) -> BudgetSnapshot;
+
+    /// Certify a candidate consume of `delta` tokens read at `read_version`.
+    /// Implementations must check solvency against the *latest* committed
+    /// state, not merely `read_version`'s state.
+    fn certify(&self, agent_id: &str, read_version: u64, delta: f64) -> Result<BudgetSnapshot, CertifyError>;
+
+    /// Force an agent's committed state back to `remaining_tokens`, bumping
+    /// its version. Used by `CostAwareRouter::set_budget`/`reset_budget` so
+    /// an explicit budget reset isn't shadowed by whatever the certifier
+    /// already had on record for that agent.
+    fn reset(&self, agent_id: &str, remaining_tokens: f64);
+}
+
+struct AgentLog {
+    version: u64,
+    remaining_tokens: f64,
+}
+
+/// In-memory certifier: one mutex per agent, acting as the ordered log the
+/// module doc describes. Correct for a single process; swap in a
+/// distributed backend via `CostAwareRouter::set_certifier` to extend the
+/// same protocol across nodes.
+#[derive(Default)]
+pub struct InMemoryBudgetCertifier {
+    logs: DashMap<String, Mutex<AgentLog>>,
+}
+
+impl BudgetCertifier for InMemoryBudgetCertifier {
+    fn read_snapshot(&self, agent_id: &str, default_remaining: f64) -> BudgetSnapshot {
+        let log = self
+            .logs
+            .entry(agent_id.to_string())
+            .or_insert_with(|| Mutex::new(AgentLog { version: 0, remaining_tokens: default_remaining }));
+        let guard = log.lock();
+        BudgetSnapshot { version: guard.version, remaining_tokens: guard.remaining_tokens }
+    }
+
+    fn certify(&self, agent_id: &str, _read_version: u64, delta: f64) -> Result<BudgetSnapshot, CertifyError> {
+        let Some(log) = self.logs.get(agent_id) else {
+            return Err(CertifyError::Conflict { agent_id: agent_id.to_string() });
+        };
+        let mut guard = log.lock();
+
+        let new_remaining = guard.remaining_tokens - delta;
+        if new_remaining < 0.0 {
+            return Err(CertifyError::InsufficientRemaining {
+                agent_id: agent_id.to_string(),
+                delta,
+                remaining: guard.remaining_tokens,
+            });
+        }
+
+        guard.remaining_tokens = new_remaining;
+        guard.version += 1;
+        Ok(BudgetSnapshot { version: guard.version, remaining_tokens: guard.remaining_tokens })
+    }
+
+    fn reset(&self, agent_id: &str, remaining_tokens: f64) {
+        let log = self
+            .logs
+            .entry(agent_id.to_string())
+            .or_insert_with(|| Mutex::new(AgentLog { version: 0, remaining_tokens }));
+        let mut guard = log.lock();
+        guard.remaining_tokens = remaining_tokens;
+        guard.version += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_certify_rejects_when_delta_exceeds_remaining() {
+        let certifier = InMemoryBudgetCertifier::default();
+        let snapshot = certifier.read_snapshot("agent-a", 10.0);
+
+        let result = certifier.certify("agent-a", snapshot.version, 15.0);
+        assert!(matches!(result, Err(CertifyError::InsufficientRemaining { .. })));
+    }
+
+    #[test]
+    fn test_certify_commits_against_latest_state_even_with_stale_read_version() {
+        let certifier = InMemoryBudgetCertifier::default();
+        let stale = certifier.read_snapshot("agent-a", 10.0);
+
+        // Someone else commits first, consuming most of the budget.
+        certifier.certify("agent-a", stale.version, 8.0).unwrap();
+
+        // A candidate computed from the now-stale snapshot still certifies
+        // correctly because the check runs against latest state, not the
+        // stale read.
+        let result = certifier.certify("agent-a", stale.version, 1.0);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().remaining_tokens, 1.0);
+
+        // But a candidate that would now overdraw is rejected.
+        let result = certifier.certify("agent-a", stale.version, 5.0);
+        assert!(matches!(result, Err(CertifyError::InsufficientRemaining { .. })));
+    }
+
+    #[test]
+    fn test_concurrent_certifies_never_drive_balance_negative() {
+        let certifier = Arc::new(InMemoryBudgetCertifier::default());
+        certifier.read_snapshot("agent-a", 100.0);
+
+        let handles: Vec<_> = (0..20)
+            .map(|_| {
+                let certifier = Arc::clone(&certifier);
+                thread::spawn(move || certifier.certify("agent-a", 0, 10.0).is_ok())
+            })
+            .collect();
+
+        let commits = handles.into_iter().filter(|h| h.join().unwrap()).count();
+        // Only 10 of the 20 candidates (100.0 / 10.0 each) can fit.
+        assert_eq!(commits, 10);
+        let final_snapshot = certifier.read_snapshot("agent-a", 100.0);
+        assert_eq!(final_snapshot.remaining_tokens, 0.0);
+    }
+}
@@ -0,0 +1,101 @@
+//! Gossip/CRDT replication of the endpoint registry across `CostAwareRouter`
+//! instances.
+//!
+//! Endpoints otherwise live only in one router's local `DashMap`, so a mesh
+//! of router instances each sees a private view of health and load: a
+//! failure recorded on one node never reaches the others. This layers a
+//! last-writer-wins register per `endpoint_id` on top of that map -- each
+//! write stamped with an `EndpointVersion` of `(logical_counter, node_id)`
+//! -- plus a pluggable [`GossipTransport`] that drives push-pull
+//! anti-entropy rounds between routers, the same last-writer-wins approach
+//! `FederationManager` uses for peer gossip (`merge_gossip`).
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+use crate::protocol::EndpointMetrics;
+
+/// Logical-clock version stamp for one endpoint's last-writer-wins record.
+/// `logical_counter` advances monotonically on every local write to that
+/// endpoint; `node_id` breaks ties between two writers that happened to
+/// reach the same counter value, so every node merging the same pair of
+/// records picks the same winner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointVersion {
+    pub logical_counter: u64,
+    pub node_id: String,
+}
+
+impl EndpointVersion {
+    /// Whether `self` should replace `other` under last-writer-wins.
+    pub(super) fn supersedes(&self, other: &EndpointVersion) -> bool {
+        (self.logical_counter, &self.node_id) > (other.logical_counter, &other.node_id)
+    }
+}
+
+/// One endpoint's full replicated state: its metrics plus the failure
+/// bookkeeping `CostAwareRouter` tracks locally. Merged field-by-field on
+/// conflict (`consecutive_failures` by max, `last_success` by latest
+/// timestamp) rather than wholesale, so a stale writer's metrics snapshot
+/// can't resurrect a failure count or success time a newer writer already
+/// moved past -- see `CostAwareRouter::merge_delta`.
+#[derive(Debug, Clone)]
+pub struct EndpointGossipRecord {
+    pub endpoint_id: String,
+    pub metrics: EndpointMetrics,
+    pub consecutive_failures: u32,
+    pub last_success: i64,
+    pub version: EndpointVersion,
+}
+
+/// Errors from a gossip anti-entropy round.
+#[derive(Error, Debug)]
+pub enum GossipError {
+    #[error("endpoint gossip exchange with peer {0} failed: {1}")]
+    ExchangeFailed(String, String),
+}
+
+/// Pluggable transport that carries one anti-entropy round between
+/// `CostAwareRouter` instances, analogous to `DiscoveryBackend`/`PeerProber`
+/// in the federation module.
+#[async_trait]
+pub trait GossipTransport: Send + Sync {
+    /// Addresses of the peer routers to gossip with this round.
+    async fn peers(&self) -> Vec<String>;
+
+    /// Send `digest` (this node's `endpoint_id -> version` view) and `push`
+    /// (every record this node currently holds) to `peer_addr`, so the peer
+    /// can both merge what it's missing from us and compute, from `digest`,
+    /// what we're missing from it. Returns the records the peer found us
+    /// missing or stale on.
+    async fn exchange(
+        &self,
+        peer_addr: &str,
+        digest: HashMap<String, EndpointVersion>,
+        push: Vec<EndpointGossipRecord>,
+    ) -> Result<Vec<EndpointGossipRecord>, GossipError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(counter: u64, node_id: &str) -> EndpointVersion {
+        EndpointVersion { logical_counter: counter, node_id: node_id.into() }
+    }
+
+    #[test]
+    fn test_supersedes_prefers_higher_counter() {
+        assert!(version(2, "a").supersedes(&version(1, "z")));
+        assert!(!version(1, "z").supersedes(&version(2, "a")));
+    }
+
+    #[test]
+    fn test_supersedes_breaks_tied_counter_by_node_id() {
+        assert!(version(5, "node-b").supersedes(&version(5, "node-a")));
+        assert!(!version(5, "node-a").supersedes(&version(5, "node-b")));
+        assert!(!version(5, "node-a").supersedes(&version(5, "node-a")));
+    }
+}
@@ -4,6 +4,7 @@
 //! and fallback chain management.
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -16,6 +17,16 @@ use crate::protocol::{
     AiMessage, EndpointMetrics, HealthStatus, RoutingDecision, BudgetInfo, RoutingScore,
 };
 
+mod certifier;
+pub mod flow;
+mod gossip;
+mod tuner;
+pub use certifier::{BudgetCertifier, BudgetSnapshot, CertifyError, InMemoryBudgetCertifier};
+use flow::MinCostFlow;
+pub use gossip::{EndpointGossipRecord, EndpointVersion, GossipError, GossipTransport};
+use tuner::{Observation, WeightTuner};
+pub use tuner::WeightTunerConfig;
+
 /// Routing errors
 #[derive(Error, Debug)]
 pub enum RoutingError {
@@ -34,6 +45,9 @@ pub enum RoutingError {
     
     #[error("Rate limit exceeded for agent: {0}")]
     RateLimitExceeded(String),
+
+    #[error("Budget certification for agent {0} kept conflicting with concurrent commits, gave up after retrying")]
+    CertificationConflict(String),
 }
 
 /// Scoring weights for endpoint selection
@@ -127,16 +141,85 @@ pub struct CostAwareRouter {
     routing_history: Arc<RwLock<Vec<RoutingDecision>>>,
     /// Router configuration
     config: RouterConfig,
+    /// Weights actually used by `score_endpoint`. Starts as `config.weights`
+    /// and is only ever swapped wholesale (never mutated field-by-field) so
+    /// a scoring pass always sees one consistent triple, even while a
+    /// `WeightTuner` is converging in the background.
+    active_weights: RwLock<ScoringWeights>,
+    /// Online weight tuner, if `set_weight_tuner` has been called.
+    tuner: RwLock<Option<WeightTuner>>,
+    /// Certifier backing `consume_budget`'s snapshot-isolation protocol.
+    /// Defaults to the in-memory, single-node-correct backend.
+    certifier: RwLock<Arc<dyn BudgetCertifier>>,
+    /// Identity used to version this node's writes to the endpoint
+    /// registry, so gossip merges can break ties between two nodes that
+    /// stamped the same logical counter (see [`EndpointVersion`]).
+    node_id: String,
+    /// Last-writer-wins version of each endpoint this node currently holds,
+    /// gossiped via [`CostAwareRouter::export_delta`]/`merge_delta`.
+    endpoint_versions: DashMap<String, EndpointVersion>,
+    /// Monotonically increasing counter used to version local endpoint writes.
+    endpoint_version_counter: AtomicU64,
 }
 
 impl CostAwareRouter {
     /// Create a new cost-aware router
     pub fn new(config: RouterConfig) -> Self {
+        let active_weights = RwLock::new(config.weights.clone());
         Self {
             endpoints: DashMap::new(),
             budgets: DashMap::new(),
             routing_history: Arc::new(RwLock::new(Vec::new())),
             config,
+            active_weights,
+            tuner: RwLock::new(None),
+            certifier: RwLock::new(Arc::new(InMemoryBudgetCertifier::default())),
+            node_id: uuid::Uuid::now_v7().to_string(),
+            endpoint_versions: DashMap::new(),
+            endpoint_version_counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Swap in a different budget certifier backend (e.g. one that
+    /// serializes consumes across nodes). The single-node default is
+    /// `InMemoryBudgetCertifier`.
+    pub fn set_certifier(&self, backend: Arc<dyn BudgetCertifier>) {
+        *self.certifier.write() = backend;
+        info!("Installed custom budget certifier backend");
+    }
+
+    /// Enable online ask/tell tuning of the scoring weights. The first
+    /// candidate becomes active immediately. Once `config.window_size`
+    /// outcomes have been observed via
+    /// `record_endpoint_success`/`record_endpoint_failure`, the candidate
+    /// becomes the incumbent if it beat it, and a new candidate perturbed
+    /// from the incumbent replaces it as the active weights either way.
+    pub fn set_weight_tuner(&self, config: WeightTunerConfig) {
+        let starting = self.active_weights.read().clone();
+        let tuner = WeightTuner::new(config, starting);
+        *self.active_weights.write() = tuner.candidate_weights();
+        *self.tuner.write() = Some(tuner);
+        info!("Enabled online weight tuning");
+    }
+
+    /// The scoring weights currently in effect: `config.weights` while
+    /// tuning is disabled, otherwise the candidate under evaluation. The
+    /// tuner's incumbent (the best triple so far) is not returned here.
+    pub fn current_weights(&self) -> ScoringWeights {
+        self.active_weights.read().clone()
+    }
+
+    /// Feed one routed message's outcome to the weight tuner, if enabled.
+    /// Swaps `active_weights` only when an evaluation window has just
+    /// completed, never mid-window.
+    fn record_tuner_observation(&self, observation: Observation) {
+        let mut guard = self.tuner.write();
+        if let Some(tuner) = guard.as_mut() {
+            if tuner.record(observation).is_some() {
+                let next_candidate = tuner.candidate_weights();
+                drop(guard);
+                *self.active_weights.write() = next_candidate;
+            }
         }
     }
     
@@ -166,9 +249,10 @@ impl CostAwareRouter {
         // 5. Build routing decision
         let (best_id, best_score, best_endpoint) = &scored[0];
         
-        let cost_score = best_endpoint.metrics.cost_per_1k_tokens * self.config.weights.cost_weight;
-        let load_score = best_endpoint.load_percentage() * 100.0 * self.config.weights.load_weight;
-        let latency_score = best_endpoint.metrics.latency_p99_ms as f64 * self.config.weights.latency_weight;
+        let weights = self.active_weights.read().clone();
+        let cost_score = best_endpoint.metrics.cost_per_1k_tokens * weights.cost_weight;
+        let load_score = best_endpoint.load_percentage() * 100.0 * weights.load_weight;
+        let latency_score = best_endpoint.metrics.latency_p99_ms as f64 * weights.latency_weight;
         
         let mut decision = RoutingDecision {
             message_id: message.message_id.clone(),
@@ -204,23 +288,171 @@ impl CostAwareRouter {
         Ok(decision)
     }
     
+    /// Route a whole batch at once via min-cost max-flow instead of scoring
+    /// each message independently.
+    ///
+    /// `route` always returns the single lowest-score endpoint, so under
+    /// concurrent load every message in a batch piles onto the one cheapest
+    /// endpoint until it saturates, ignoring the spare capacity of
+    /// slightly-more-expensive peers. This builds a flow network (source ->
+    /// one node per message -> every healthy, affordable endpoint -> sink,
+    /// sink edges capped by remaining capacity) and solves successive
+    /// shortest augmenting paths with Johnson potentials to spread the batch
+    /// across endpoints at globally-optimal total score.
+    ///
+    /// If total remaining capacity can't cover the whole batch, the messages
+    /// that fit are assigned by the flow solve and the remainder is routed
+    /// greedily (ignoring capacity) via the same scoring `route` uses.
+    pub async fn route_batch(&self, messages: &[AiMessage]) -> Result<Vec<RoutingDecision>, RoutingError> {
+        if messages.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        for message in messages {
+            self.check_budget(&message.agent_id, message.estimated_cost_tokens)?;
+        }
+
+        let endpoints: Vec<Endpoint> = self
+            .get_healthy_endpoints()
+            .into_iter()
+            .filter(|e| e.metrics.capacity > e.metrics.current_load)
+            .collect();
+        if endpoints.is_empty() {
+            return Err(RoutingError::NoHealthyEndpoints);
+        }
+
+        let total_remaining: u32 = endpoints
+            .iter()
+            .map(|e| e.metrics.capacity - e.metrics.current_load)
+            .sum();
+        let flow_count = (total_remaining as usize).min(messages.len());
+        let (flow_messages, overflow_messages) = messages.split_at(flow_count);
+
+        let mut decisions = self.solve_batch_flow(flow_messages, &endpoints).await?;
+        for message in overflow_messages {
+            decisions.push(self.route(message).await?);
+        }
+
+        if !overflow_messages.is_empty() {
+            warn!(
+                batch_size = messages.len(),
+                capacity = total_remaining,
+                overflow = overflow_messages.len(),
+                "Batch exceeds remaining endpoint capacity, routed overflow greedily"
+            );
+        }
+
+        Ok(decisions)
+    }
+
+    /// Solve the min-cost max-flow assignment for a batch that fits within
+    /// available endpoint capacity and turn the resulting flow into
+    /// [`RoutingDecision`]s.
+    async fn solve_batch_flow(&self, messages: &[AiMessage], endpoints: &[Endpoint]) -> Result<Vec<RoutingDecision>, RoutingError> {
+        if messages.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Node layout: 0 = source, 1..=messages.len() = message nodes,
+        // then one node per endpoint, then the sink.
+        let source = 0;
+        let message_base = 1;
+        let endpoint_base = message_base + messages.len();
+        let sink = endpoint_base + endpoints.len();
+
+        let mut flow = MinCostFlow::new(sink + 1);
+        let mut message_edges: Vec<Vec<(usize, usize)>> = vec![Vec::new(); messages.len()]; // (endpoint idx, edge idx)
+
+        for (i, message) in messages.iter().enumerate() {
+            flow.add_edge(source, message_base + i, 1, 0.0);
+
+            for (j, endpoint) in endpoints.iter().enumerate() {
+                let estimated_cost = endpoint.metrics.cost_per_1k_tokens * message.estimated_cost_tokens / 1000.0;
+                if self.check_budget(&message.agent_id, estimated_cost).is_err() {
+                    continue;
+                }
+                let score = self.score_endpoint(endpoint);
+                let edge_idx = flow.add_edge(message_base + i, endpoint_base + j, 1, score);
+                message_edges[i].push((j, edge_idx));
+            }
+        }
+
+        for (j, endpoint) in endpoints.iter().enumerate() {
+            let remaining = (endpoint.metrics.capacity - endpoint.metrics.current_load) as i64;
+            flow.add_edge(endpoint_base + j, sink, remaining, 0.0);
+        }
+
+        let pushed = flow.solve(source, sink, messages.len() as i64);
+
+        let mut decisions = Vec::with_capacity(messages.len());
+        for (i, message) in messages.iter().enumerate() {
+            let assigned = message_edges[i].iter().find(|(_, edge_idx)| flow.flow_on(*edge_idx) > 0);
+            match assigned {
+                Some((j, _)) => decisions.push(self.build_decision(message, &endpoints[*j])),
+                // No endpoint could carry this message's unit of flow (e.g. no
+                // endpoint it could afford) even though capacity existed overall.
+                None => decisions.push(self.route(message).await?),
+            }
+        }
+
+        debug!(batch_size = messages.len(), pushed, "Resolved batch routing via min-cost flow");
+        Ok(decisions)
+    }
+
+    /// Build a [`RoutingDecision`] for a message already assigned to `endpoint`,
+    /// mirroring the scoring breakdown `route` produces.
+    fn build_decision(&self, message: &AiMessage, endpoint: &Endpoint) -> RoutingDecision {
+        let score = self.score_endpoint(endpoint);
+        let weights = self.active_weights.read().clone();
+        let cost_score = endpoint.metrics.cost_per_1k_tokens * weights.cost_weight;
+        let load_score = endpoint.load_percentage() * 100.0 * weights.load_weight;
+        let latency_score = endpoint.metrics.latency_p99_ms as f64 * weights.latency_weight;
+
+        let decision = RoutingDecision {
+            message_id: message.message_id.clone(),
+            target_endpoint: endpoint.metrics.endpoint_id.clone(),
+            estimated_latency_ms: endpoint.metrics.latency_p99_ms as i32,
+            estimated_cost: endpoint.metrics.cost_per_1k_tokens * message.estimated_cost_tokens / 1000.0,
+            routing_reason: format!(
+                "Batch-assigned via min-cost flow, score {:.4} (cost: {:.2}, load: {:.0}%, latency: {:.0}ms)",
+                score,
+                endpoint.metrics.cost_per_1k_tokens,
+                endpoint.load_percentage() * 100.0,
+                endpoint.metrics.latency_p99_ms
+            ),
+            fallback_endpoints: Vec::new(),
+            score_breakdown: Some(RoutingScore {
+                cost_score,
+                load_score,
+                latency_score,
+                total_score: cost_score + load_score + latency_score,
+            }),
+        };
+
+        self.record_decision(&decision);
+        decision
+    }
+
     /// Register an endpoint
     pub fn register_endpoint(&self, metrics: EndpointMetrics) {
         let endpoint_id = metrics.endpoint_id.clone();
         self.endpoints.insert(endpoint_id.clone(), Endpoint::new(metrics));
+        self.bump_endpoint_version(&endpoint_id);
         info!(endpoint = %endpoint_id, "Registered endpoint");
     }
-    
+
     /// Update endpoint metrics
     pub fn update_endpoint_metrics(&self, endpoint_id: &str, metrics: EndpointMetrics) -> Result<(), RoutingError> {
         if let Some(mut entry) = self.endpoints.get_mut(endpoint_id) {
             entry.metrics = metrics;
+            drop(entry);
+            self.bump_endpoint_version(endpoint_id);
             Ok(())
         } else {
             Err(RoutingError::EndpointNotFound(endpoint_id.to_string()))
         }
     }
-    
+
     /// Mark an endpoint as failed
     pub fn record_endpoint_failure(&self, endpoint_id: &str) {
         if let Some(mut entry) = self.endpoints.get_mut(endpoint_id) {
@@ -229,16 +461,24 @@ impl CostAwareRouter {
                 entry.metrics.health_status = HealthStatus::Unhealthy as i32;
                 warn!(endpoint = %endpoint_id, "Endpoint marked unhealthy");
             }
+            drop(entry);
+            self.bump_endpoint_version(endpoint_id);
         }
+        self.record_tuner_observation(Observation { latency_ms: 0.0, realized_cost: 0.0, is_error: true });
     }
-    
-    /// Mark an endpoint as successful
-    pub fn record_endpoint_success(&self, endpoint_id: &str) {
+
+    /// Mark an endpoint as successful, recording the observed latency and
+    /// realized cost of the message it served so the weight tuner (if
+    /// enabled) can factor this outcome into its current evaluation window.
+    pub fn record_endpoint_success(&self, endpoint_id: &str, observed_latency_ms: f64, realized_cost: f64) {
         if let Some(mut entry) = self.endpoints.get_mut(endpoint_id) {
             entry.consecutive_failures = 0;
             entry.last_success = Endpoint::now_ns();
             entry.metrics.health_status = HealthStatus::Healthy as i32;
+            drop(entry);
+            self.bump_endpoint_version(endpoint_id);
         }
+        self.record_tuner_observation(Observation { latency_ms: observed_latency_ms, realized_cost, is_error: false });
     }
     
     /// Set budget for an agent
@@ -250,24 +490,46 @@ impl CostAwareRouter {
             consumption_rate: 0.0,
             reset_at,
         });
+        self.certifier.read().reset(agent_id, initial_tokens);
     }
     
-    /// Consume tokens from an agent's budget
+    /// Consume tokens from an agent's budget via read-snapshot /
+    /// certify-or-abort: reads a `(version, remaining_tokens)` snapshot,
+    /// then submits `(agent_id, read_version, tokens)` to the certifier,
+    /// which is the one place that decides commit-or-abort against the
+    /// *latest* committed state. On `Conflict` (a distributed certifier
+    /// racing another cohort's commit) retries with a fresh snapshot a
+    /// bounded number of times; the in-memory default never conflicts.
     pub fn consume_budget(&self, agent_id: &str, tokens: f64) -> Result<f64, RoutingError> {
-        if let Some(mut budget) = self.budgets.get_mut(agent_id) {
-            if budget.remaining_tokens < tokens {
-                return Err(RoutingError::BudgetExceeded {
-                    agent_id: agent_id.to_string(),
-                    required: tokens,
-                    available: budget.remaining_tokens,
-                });
+        let Some(initial_remaining) = self.budgets.get(agent_id).map(|b| b.remaining_tokens) else {
+            // No budget set for this agent, allow unlimited.
+            return Ok(f64::MAX);
+        };
+
+        const MAX_RETRIES: u32 = 5;
+        let certifier = self.certifier.read().clone();
+
+        for _ in 0..MAX_RETRIES {
+            let snapshot = certifier.read_snapshot(agent_id, initial_remaining);
+            match certifier.certify(agent_id, snapshot.version, tokens) {
+                Ok(committed) => {
+                    if let Some(mut budget) = self.budgets.get_mut(agent_id) {
+                        budget.remaining_tokens = committed.remaining_tokens;
+                    }
+                    return Ok(committed.remaining_tokens);
+                }
+                Err(CertifyError::InsufficientRemaining { remaining, .. }) => {
+                    return Err(RoutingError::BudgetExceeded {
+                        agent_id: agent_id.to_string(),
+                        required: tokens,
+                        available: remaining,
+                    });
+                }
+                Err(CertifyError::Conflict { .. }) => continue,
             }
-            budget.remaining_tokens -= tokens;
-            Ok(budget.remaining_tokens)
-        } else {
-            // No budget set, allow unlimited
-            Ok(f64::MAX)
         }
+
+        Err(RoutingError::CertificationConflict(agent_id.to_string()))
     }
     
     /// Get remaining budget for an agent
@@ -277,6 +539,25 @@ impl CostAwareRouter {
             .map(|b| b.remaining_tokens)
             .unwrap_or(f64::MAX)
     }
+
+    /// Fraction of an agent's initial budget still remaining, in `[0.0,
+    /// 1.0]`. Used by the transport layer to scale per-connection
+    /// flow-control limits (see `TransportLayer::apply_budget_flow_control`)
+    /// down as an agent nears its ceiling. An agent with no budget set, or
+    /// one whose initial allotment was zero, is always reported at `1.0`
+    /// (unthrottled).
+    pub fn budget_ratio(&self, agent_id: &str) -> f64 {
+        self.budgets
+            .get(agent_id)
+            .map(|b| {
+                if b.initial_tokens <= 0.0 {
+                    1.0
+                } else {
+                    (b.remaining_tokens / b.initial_tokens).clamp(0.0, 1.0)
+                }
+            })
+            .unwrap_or(1.0)
+    }
     
     /// Get all healthy endpoints
     fn get_healthy_endpoints(&self) -> Vec<Endpoint> {
@@ -289,10 +570,11 @@ impl CostAwareRouter {
     
     /// Score an endpoint (lower is better)
     fn score_endpoint(&self, endpoint: &Endpoint) -> f64 {
-        let cost_score = endpoint.metrics.cost_per_1k_tokens * self.config.weights.cost_weight;
-        let load_score = endpoint.load_percentage() * 100.0 * self.config.weights.load_weight;
-        let latency_score = endpoint.metrics.latency_p99_ms as f64 * self.config.weights.latency_weight;
-        
+        let weights = self.active_weights.read();
+        let cost_score = endpoint.metrics.cost_per_1k_tokens * weights.cost_weight;
+        let load_score = endpoint.load_percentage() * 100.0 * weights.load_weight;
+        let latency_score = endpoint.metrics.latency_p99_ms as f64 * weights.latency_weight;
+
         cost_score + load_score + latency_score
     }
     
@@ -340,6 +622,7 @@ impl CostAwareRouter {
     
     /// Remove an endpoint
     pub fn remove_endpoint(&self, endpoint_id: &str) -> bool {
+        self.endpoint_versions.remove(endpoint_id);
         self.endpoints.remove(endpoint_id).is_some()
     }
     
@@ -347,6 +630,7 @@ impl CostAwareRouter {
     pub fn reset_budget(&self, agent_id: &str) {
         if let Some(mut budget) = self.budgets.get_mut(agent_id) {
             budget.remaining_tokens = budget.initial_tokens;
+            self.certifier.read().reset(agent_id, budget.initial_tokens);
             info!(agent = %agent_id, tokens = budget.initial_tokens, "Reset budget");
         }
     }
@@ -377,6 +661,139 @@ impl CostAwareRouter {
             .map(|e| e.metrics.endpoint_id.clone())
             .collect()
     }
+
+    /// Stamp `endpoint_id` with a fresh, locally-unique [`EndpointVersion`]
+    /// after a local write, so a gossip peer merging our state afterwards
+    /// sees it as newer than whatever it already holds.
+    fn bump_endpoint_version(&self, endpoint_id: &str) -> EndpointVersion {
+        let counter = self.endpoint_version_counter.fetch_add(1, Ordering::Relaxed) + 1;
+        let version = EndpointVersion { logical_counter: counter, node_id: self.node_id.clone() };
+        self.endpoint_versions.insert(endpoint_id.to_string(), version.clone());
+        version
+    }
+
+    /// Build the replicated record for `endpoint_id`, bundling its current
+    /// metrics and failure bookkeeping with its gossip version.
+    fn endpoint_record(&self, endpoint_id: &str) -> Option<EndpointGossipRecord> {
+        let endpoint = self.endpoints.get(endpoint_id)?;
+        let version = self.endpoint_versions.get(endpoint_id)?.clone();
+        Some(EndpointGossipRecord {
+            endpoint_id: endpoint_id.to_string(),
+            metrics: endpoint.metrics.clone(),
+            consecutive_failures: endpoint.consecutive_failures,
+            last_success: endpoint.last_success,
+            version,
+        })
+    }
+
+    /// This node's current `endpoint_id -> version` view, sent as the
+    /// digest half of a gossip anti-entropy round.
+    pub fn endpoint_digest(&self) -> HashMap<String, EndpointVersion> {
+        self.endpoint_versions.iter().map(|e| (e.key().clone(), e.value().clone())).collect()
+    }
+
+    /// Every endpoint record whose local version is newer than `since`
+    /// (pass `0` for the full registry), used as the push half of a gossip
+    /// round.
+    pub fn export_delta(&self, since: u64) -> Vec<EndpointGossipRecord> {
+        self.endpoint_versions
+            .iter()
+            .filter(|e| e.value().logical_counter > since)
+            .filter_map(|e| self.endpoint_record(e.key()))
+            .collect()
+    }
+
+    /// The records a remote `endpoint_digest` is missing or holds a stale
+    /// version of, to answer a peer's gossip round.
+    pub fn records_missing_from(&self, remote_digest: &HashMap<String, EndpointVersion>) -> Vec<EndpointGossipRecord> {
+        self.endpoint_versions
+            .iter()
+            .filter(|e| match remote_digest.get(e.key()) {
+                Some(remote_version) => e.value().supersedes(remote_version),
+                None => true,
+            })
+            .filter_map(|e| self.endpoint_record(e.key()))
+            .collect()
+    }
+
+    /// Merge a batch of gossiped endpoint records using last-writer-wins on
+    /// `version`: a record is applied if its version supersedes what we
+    /// hold for that `endpoint_id`. On apply, `metrics` replaces our copy
+    /// wholesale but `consecutive_failures` is merged by max and
+    /// `last_success` by the latest timestamp, so a stale writer's metrics
+    /// snapshot can't roll back health bookkeeping a newer writer already
+    /// advanced. Returns the number of records actually applied.
+    pub fn merge_delta(&self, entries: Vec<EndpointGossipRecord>) -> usize {
+        let mut applied = 0;
+        for entry in entries {
+            let should_apply = match self.endpoint_versions.get(&entry.endpoint_id) {
+                Some(existing) => entry.version.supersedes(&existing),
+                None => true,
+            };
+
+            if should_apply {
+                self.endpoint_versions.insert(entry.endpoint_id.clone(), entry.version.clone());
+                self.endpoints
+                    .entry(entry.endpoint_id.clone())
+                    .and_modify(|e| {
+                        e.consecutive_failures = e.consecutive_failures.max(entry.consecutive_failures);
+                        e.last_success = e.last_success.max(entry.last_success);
+                        e.metrics = entry.metrics.clone();
+                    })
+                    .or_insert_with(|| Endpoint {
+                        metrics: entry.metrics.clone(),
+                        consecutive_failures: entry.consecutive_failures,
+                        last_success: entry.last_success,
+                    });
+                applied += 1;
+            }
+        }
+        applied
+    }
+
+    /// Run one anti-entropy round against every peer `transport` reports:
+    /// push our full registry plus our digest, and merge back whatever the
+    /// peer's comparison found us missing or stale on.
+    async fn gossip_round(&self, transport: &dyn GossipTransport) {
+        let peers = transport.peers().await;
+        if peers.is_empty() {
+            return;
+        }
+
+        let digest = self.endpoint_digest();
+        let push = self.export_delta(0);
+
+        for peer_addr in peers {
+            match transport.exchange(&peer_addr, digest.clone(), push.clone()).await {
+                Ok(missing) => {
+                    let applied = self.merge_delta(missing);
+                    if applied > 0 {
+                        debug!(peer = %peer_addr, applied, "Merged endpoint gossip reply");
+                    }
+                }
+                Err(e) => warn!(peer = %peer_addr, error = %e, "Endpoint gossip exchange failed"),
+            }
+        }
+    }
+
+    /// Start a background task that runs an endpoint-registry anti-entropy
+    /// round against `transport`'s peers every `interval`, so
+    /// `get_healthy_endpoints` (and therefore `route`) reflects cluster-wide
+    /// health and load instead of only what this node observed directly.
+    pub fn with_gossip(
+        self: &Arc<Self>,
+        transport: Arc<dyn GossipTransport>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let router = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                router.gossip_round(transport.as_ref()).await;
+            }
+        })
+    }
 }
 
 /// Router statistics
@@ -463,8 +880,230 @@ mod tests {
         );
         
         let decision = router.route(&msg).await.unwrap();
-        
+
         assert_eq!(decision.target_endpoint, "primary");
         assert_eq!(decision.fallback_endpoints.len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_route_batch_spreads_across_endpoints_instead_of_herding() {
+        let router = CostAwareRouter::new(RouterConfig::default());
+
+        // "cheap" only has room for 2 before it hits capacity; the rest of
+        // a 5-message batch must spill onto "pricier" instead of queuing.
+        router.register_endpoint(create_test_endpoint("cheap", 1.0, 0, 2, 5.0));
+        router.register_endpoint(create_test_endpoint("pricier", 5.0, 0, 10, 5.0));
+
+        let messages: Vec<AiMessage> = (0..5)
+            .map(|_| AiMessage::new("batch-agent".to_string(), b"test".to_vec(), 100.0, i64::MAX))
+            .collect();
+
+        let decisions = router.route_batch(&messages).await.unwrap();
+        assert_eq!(decisions.len(), 5);
+
+        let cheap_count = decisions.iter().filter(|d| d.target_endpoint == "cheap").count();
+        let pricier_count = decisions.iter().filter(|d| d.target_endpoint == "pricier").count();
+        assert_eq!(cheap_count, 2);
+        assert_eq!(pricier_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_route_batch_routes_overflow_greedily_past_total_capacity() {
+        let router = CostAwareRouter::new(RouterConfig::default());
+        router.register_endpoint(create_test_endpoint("only", 1.0, 0, 1, 5.0));
+
+        let messages: Vec<AiMessage> = (0..3)
+            .map(|_| AiMessage::new("batch-agent".to_string(), b"test".to_vec(), 100.0, i64::MAX))
+            .collect();
+
+        let decisions = router.route_batch(&messages).await.unwrap();
+
+        // Capacity 1 can't cover a batch of 3; the overflow is still routed
+        // (greedily, past capacity) rather than dropped.
+        assert_eq!(decisions.len(), 3);
+        assert!(decisions.iter().all(|d| d.target_endpoint == "only"));
+    }
+
+    #[tokio::test]
+    async fn test_route_batch_errors_when_no_healthy_endpoints() {
+        let router = CostAwareRouter::new(RouterConfig::default());
+        let messages = vec![AiMessage::new("batch-agent".to_string(), b"test".to_vec(), 100.0, i64::MAX)];
+
+        let result = router.route_batch(&messages).await;
+        assert!(matches!(result, Err(RoutingError::NoHealthyEndpoints)));
+    }
+
+    #[test]
+    fn test_consume_budget_never_goes_negative_via_certifier() {
+        let router = CostAwareRouter::new(RouterConfig::default());
+        router.set_budget("agent-a", 100.0, i64::MAX);
+
+        assert_eq!(router.consume_budget("agent-a", 60.0).unwrap(), 40.0);
+        let result = router.consume_budget("agent-a", 60.0);
+        assert!(matches!(result, Err(RoutingError::BudgetExceeded { available, .. }) if available == 40.0));
+        assert_eq!(router.get_remaining_budget("agent-a"), 40.0);
+    }
+
+    #[test]
+    fn test_budget_ratio_tracks_remaining_fraction_of_initial() {
+        let router = CostAwareRouter::new(RouterConfig::default());
+        router.set_budget("agent-a", 100.0, i64::MAX);
+        assert_eq!(router.budget_ratio("agent-a"), 1.0);
+
+        router.consume_budget("agent-a", 75.0).unwrap();
+        assert_eq!(router.budget_ratio("agent-a"), 0.25);
+
+        // No budget ever set for this agent: treated as unthrottled.
+        assert_eq!(router.budget_ratio("never-seen"), 1.0);
+    }
+
+    #[test]
+    fn test_reset_budget_reseeds_certifier_state() {
+        let router = CostAwareRouter::new(RouterConfig::default());
+        router.set_budget("agent-a", 100.0, i64::MAX);
+        router.consume_budget("agent-a", 90.0).unwrap();
+
+        router.reset_budget("agent-a");
+
+        // Without re-seeding the certifier, this would still see the
+        // drained balance from before the reset and reject the consume.
+        assert_eq!(router.consume_budget("agent-a", 90.0).unwrap(), 10.0);
+    }
+
+    #[test]
+    fn test_set_certifier_swaps_to_independent_backend() {
+        let router = CostAwareRouter::new(RouterConfig::default());
+        router.set_budget("agent-a", 100.0, i64::MAX);
+        router.consume_budget("agent-a", 50.0).unwrap();
+
+        router.set_certifier(Arc::new(InMemoryBudgetCertifier::default()));
+
+        // The fresh backend has never seen "agent-a", so it re-seeds from
+        // `budgets`' last-known remaining (50.0) rather than the old
+        // certifier's internal log.
+        assert_eq!(router.consume_budget("agent-a", 50.0).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_set_weight_tuner_activates_first_candidate_immediately() {
+        let router = CostAwareRouter::new(RouterConfig::default());
+        let before = router.current_weights();
+
+        router.set_weight_tuner(WeightTunerConfig { window_size: 10, ..WeightTunerConfig::default() });
+
+        // A candidate should already be active, perturbed away from the
+        // router's starting weights (vanishingly unlikely to match exactly).
+        let after = router.current_weights();
+        assert_ne!((before.cost_weight, before.load_weight), (after.cost_weight, after.load_weight));
+        let sum = after.cost_weight + after.load_weight + after.latency_weight;
+        assert!((sum - 1.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_weight_tuner_swaps_weights_only_after_window_completes() {
+        let router = CostAwareRouter::new(RouterConfig::default());
+        router.register_endpoint(create_test_endpoint("endpoint-1", 1.0, 0, 100, 5.0));
+        router.set_weight_tuner(WeightTunerConfig { window_size: 3, ..WeightTunerConfig::default() });
+
+        let mid_window = router.current_weights();
+        router.record_endpoint_success("endpoint-1", 5.0, 0.1);
+        router.record_endpoint_success("endpoint-1", 5.0, 0.1);
+        // Still mid-window: active weights must not have moved yet.
+        assert_eq!(router.current_weights().cost_weight, mid_window.cost_weight);
+
+        router.record_endpoint_success("endpoint-1", 5.0, 0.1);
+        // Window just completed: a (possibly new) candidate is now active.
+        let sum_after = {
+            let w = router.current_weights();
+            w.cost_weight + w.load_weight + w.latency_weight
+        };
+        assert!((sum_after - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_merge_delta_ignores_stale_version() {
+        let router = CostAwareRouter::new(RouterConfig::default());
+        router.register_endpoint(create_test_endpoint("endpoint-1", 1.0, 0, 100, 5.0));
+        let current_version = router.endpoint_digest().remove("endpoint-1").unwrap();
+
+        let mut stale_metrics = create_test_endpoint("endpoint-1", 1.0, 0, 100, 5.0);
+        stale_metrics.health_status = HealthStatus::Unhealthy as i32;
+        let applied = router.merge_delta(vec![EndpointGossipRecord {
+            endpoint_id: "endpoint-1".into(),
+            metrics: stale_metrics,
+            consecutive_failures: 99,
+            last_success: 0,
+            version: EndpointVersion { logical_counter: 0, node_id: "other-node".into() },
+        }]);
+
+        assert_eq!(applied, 0);
+        assert_ne!(current_version.logical_counter, 0);
+        assert!(router.endpoints.get("endpoint-1").unwrap().is_healthy());
+    }
+
+    #[test]
+    fn test_merge_delta_merges_failures_by_max_and_success_by_latest() {
+        let router = CostAwareRouter::new(RouterConfig::default());
+        router.register_endpoint(create_test_endpoint("endpoint-1", 1.0, 0, 100, 5.0));
+        router.record_endpoint_failure("endpoint-1");
+        let local_last_success = router.endpoints.get("endpoint-1").unwrap().last_success;
+
+        let applied = router.merge_delta(vec![EndpointGossipRecord {
+            endpoint_id: "endpoint-1".into(),
+            metrics: create_test_endpoint("endpoint-1", 1.0, 0, 100, 5.0),
+            consecutive_failures: 5,
+            last_success: local_last_success + 1_000,
+            version: EndpointVersion { logical_counter: 999, node_id: "other-node".into() },
+        }]);
+
+        assert_eq!(applied, 1);
+        let merged = router.endpoints.get("endpoint-1").unwrap();
+        // Max of our 1 failure and the remote's 5.
+        assert_eq!(merged.consecutive_failures, 5);
+        assert_eq!(merged.last_success, local_last_success + 1_000);
+    }
+
+    /// Transport that gossips directly against another in-process router,
+    /// enough to exercise `with_gossip`'s round-trip without a real network.
+    struct DirectTransport {
+        peer: Arc<CostAwareRouter>,
+    }
+
+    #[async_trait::async_trait]
+    impl GossipTransport for DirectTransport {
+        async fn peers(&self) -> Vec<String> {
+            vec!["peer".to_string()]
+        }
+
+        async fn exchange(
+            &self,
+            _peer_addr: &str,
+            digest: HashMap<String, EndpointVersion>,
+            push: Vec<EndpointGossipRecord>,
+        ) -> Result<Vec<EndpointGossipRecord>, GossipError> {
+            self.peer.merge_delta(push);
+            Ok(self.peer.records_missing_from(&digest))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gossip_round_converges_health_between_two_routers() {
+        let node_a = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+        let node_b = Arc::new(CostAwareRouter::new(RouterConfig::default()));
+
+        node_a.register_endpoint(create_test_endpoint("endpoint-1", 1.0, 0, 100, 5.0));
+        for _ in 0..node_a.config.unhealthy_threshold {
+            node_a.record_endpoint_failure("endpoint-1");
+        }
+        assert!(!node_a.endpoints.get("endpoint-1").unwrap().is_healthy());
+
+        node_b.register_endpoint(create_test_endpoint("endpoint-2", 2.0, 0, 100, 5.0));
+
+        node_a.gossip_round(&DirectTransport { peer: Arc::clone(&node_b) }).await;
+
+        // node_b learned endpoint-1's (unhealthy) state from node_a...
+        assert!(!node_b.endpoints.get("endpoint-1").unwrap().is_healthy());
+        // ...and node_a, via the reply, learned about endpoint-2.
+        assert!(node_a.endpoints.get("endpoint-2").unwrap().is_healthy());
+    }
 }
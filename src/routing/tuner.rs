@@ -0,0 +1,212 @@
+//! Online ask/tell tuning of [`super::ScoringWeights`] via a 1+1 evolution
+//! strategy, mirroring the optimization-runner pattern of a kurobako study:
+//! `ask` proposes the next candidate to trial, `tell` scores how the just-
+//! completed window performed and decides whether to keep it.
+
+use std::collections::VecDeque;
+
+use super::ScoringWeights;
+
+/// Configuration for [`WeightTuner`].
+#[derive(Debug, Clone)]
+pub struct WeightTunerConfig {
+    /// Number of routed messages to observe before scoring a candidate.
+    pub window_size: usize,
+    /// Initial standard deviation of the Gaussian perturbation applied to
+    /// the incumbent weights.
+    pub initial_sigma: f64,
+    /// Multiplier applied to `sigma` after every evaluated round so later
+    /// candidates converge around the incumbent instead of roaming widely.
+    pub sigma_decay: f64,
+    /// Weight applied to `mean_realized_cost` in the objective.
+    pub cost_penalty: f64,
+    /// Weight applied to `error_rate` in the objective.
+    pub error_penalty: f64,
+}
+
+impl Default for WeightTunerConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 200,
+            initial_sigma: 0.1,
+            sigma_decay: 0.97,
+            cost_penalty: 1.0,
+            error_penalty: 100.0,
+        }
+    }
+}
+
+/// One routed message's observed outcome, fed in via
+/// `CostAwareRouter::record_endpoint_success`/`record_endpoint_failure`.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Observation {
+    pub latency_ms: f64,
+    pub realized_cost: f64,
+    pub is_error: bool,
+}
+
+/// Tiny self-contained xorshift64 PRNG, seeded from the clock, used only to
+/// draw the Gaussian perturbations below — not suitable for anything
+/// security-sensitive.
+struct Rng(u64);
+
+impl Rng {
+    fn seeded() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x9E3779B97F4A7C15);
+        Self(nanos | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal sample via the Box-Muller transform.
+    fn next_gaussian(&mut self) -> f64 {
+        let u1 = self.next_f64().max(f64::MIN_POSITIVE);
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+/// Ask/tell study over candidate `ScoringWeights` triples, run as a 1+1
+/// evolution strategy: the only competition a candidate faces is the
+/// current incumbent.
+pub(super) struct WeightTuner {
+    config: WeightTunerConfig,
+    rng: Rng,
+    incumbent: ScoringWeights,
+    incumbent_objective: f64,
+    candidate: ScoringWeights,
+    sigma: f64,
+    window: VecDeque<Observation>,
+}
+
+impl WeightTuner {
+    pub fn new(config: WeightTunerConfig, starting_weights: ScoringWeights) -> Self {
+        let sigma = config.initial_sigma;
+        let mut rng = Rng::seeded();
+        let candidate = Self::perturb(&mut rng, &starting_weights, sigma);
+        Self {
+            config,
+            rng,
+            incumbent: starting_weights,
+            incumbent_objective: f64::INFINITY,
+            candidate,
+            sigma,
+            window: VecDeque::new(),
+        }
+    }
+
+    /// Weights the caller should score endpoints with right now.
+    pub fn candidate_weights(&self) -> ScoringWeights {
+        self.candidate.clone()
+    }
+
+    /// Record one routed message's outcome. Returns `Some(incumbent)` (the
+    /// best weights so far) once the evaluation window fills and a decision
+    /// has been made; the next candidate to trial is then available from
+    /// `candidate_weights`, so the caller can swap the router's active
+    /// weights atomically between windows rather than mid-window.
+    pub fn record(&mut self, observation: Observation) -> Option<ScoringWeights> {
+        self.window.push_back(observation);
+        if self.window.len() < self.config.window_size {
+            return None;
+        }
+
+        let n = self.window.len() as f64;
+        let mean_latency_ms = self.window.iter().map(|o| o.latency_ms).sum::<f64>() / n;
+        let mean_realized_cost = self.window.iter().map(|o| o.realized_cost).sum::<f64>() / n;
+        let error_rate = self.window.iter().filter(|o| o.is_error).count() as f64 / n;
+
+        let objective = mean_latency_ms
+            + self.config.cost_penalty * mean_realized_cost
+            + self.config.error_penalty * error_rate;
+
+        if objective < self.incumbent_objective {
+            self.incumbent = self.candidate.clone();
+            self.incumbent_objective = objective;
+        }
+
+        self.window.clear();
+        self.sigma *= self.config.sigma_decay;
+        self.candidate = Self::perturb(&mut self.rng, &self.incumbent, self.sigma);
+
+        Some(self.incumbent.clone())
+    }
+
+    /// Perturb `base` with Gaussian noise, renormalize so the triple sums to
+    /// 1, then clamp each weight back into `[0, 1]`.
+    fn perturb(rng: &mut Rng, base: &ScoringWeights, sigma: f64) -> ScoringWeights {
+        let mut cost = (base.cost_weight + rng.next_gaussian() * sigma).clamp(0.0, 1.0);
+        let mut load = (base.load_weight + rng.next_gaussian() * sigma).clamp(0.0, 1.0);
+        let mut latency = (base.latency_weight + rng.next_gaussian() * sigma).clamp(0.0, 1.0);
+
+        let sum = cost + load + latency;
+        if sum > 0.0 {
+            cost /= sum;
+            load /= sum;
+            latency /= sum;
+        } else {
+            // Degenerate all-zero draw: fall back to the base triple untouched.
+            cost = base.cost_weight;
+            load = base.load_weight;
+            latency = base.latency_weight;
+        }
+
+        ScoringWeights { cost_weight: cost, load_weight: load, latency_weight: latency }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_returns_none_until_window_fills() {
+        let mut tuner = WeightTuner::new(
+            WeightTunerConfig { window_size: 3, ..WeightTunerConfig::default() },
+            ScoringWeights::default(),
+        );
+
+        assert!(tuner.record(Observation { latency_ms: 10.0, realized_cost: 1.0, is_error: false }).is_none());
+        assert!(tuner.record(Observation { latency_ms: 10.0, realized_cost: 1.0, is_error: false }).is_none());
+        assert!(tuner.record(Observation { latency_ms: 10.0, realized_cost: 1.0, is_error: false }).is_some());
+    }
+
+    #[test]
+    fn test_perturbed_weights_always_sum_to_one() {
+        let mut rng = Rng::seeded();
+        let base = ScoringWeights { cost_weight: 0.4, load_weight: 0.3, latency_weight: 0.3 };
+        let perturbed = WeightTuner::perturb(&mut rng, &base, 0.5);
+
+        let sum = perturbed.cost_weight + perturbed.load_weight + perturbed.latency_weight;
+        assert!((sum - 1.0).abs() < 1e-9);
+        assert!(perturbed.cost_weight >= 0.0 && perturbed.cost_weight <= 1.0);
+    }
+
+    #[test]
+    fn test_incumbent_only_adopts_candidate_with_better_objective() {
+        let mut tuner = WeightTuner::new(
+            WeightTunerConfig { window_size: 1, ..WeightTunerConfig::default() },
+            ScoringWeights::default(),
+        );
+
+        // First window always sets the incumbent objective baseline.
+        let first = tuner.record(Observation { latency_ms: 5.0, realized_cost: 0.1, is_error: false }).unwrap();
+
+        // A much worse window (lots of errors) must not replace the incumbent.
+        let second = tuner.record(Observation { latency_ms: 5.0, realized_cost: 0.1, is_error: true }).unwrap();
+        assert_eq!(first.cost_weight, second.cost_weight);
+        assert_eq!(first.load_weight, second.load_weight);
+    }
+}
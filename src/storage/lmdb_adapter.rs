@@ -0,0 +1,229 @@
+//! Embedded LMDB [`StorageEngine`] adapter.
+//!
+//! A second embedded option alongside [`super::sqlite_adapter`], for
+//! deployments that prefer an mmap'd B+-tree store over a SQL file. Same
+//! crash-durability guarantee, same trait surface; pick whichever single-node
+//! storage engine fits the operator's ops tooling.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use heed::types::{Bytes, Str, U64};
+use heed::{Database, Env, EnvOpenOptions};
+use prost::Message;
+
+use crate::protocol::{AiMessage, BudgetInfo, TaskState};
+
+use super::{SearchResult, StorageEngine, StorageError};
+
+const DOCUMENT_KEY_SEP: char = '\u{1}';
+
+/// Embedded, crash-durable backend backed by an LMDB environment.
+pub struct LmdbEngine {
+    env: Env,
+    messages: Database<Str, Bytes>,
+    task_states: Database<Str, Bytes>,
+    budgets: Database<Str, Bytes>,
+    documents: Database<Str, Bytes>,
+    nodes: Database<U64<heed::byteorder::BigEndian>, Str>,
+    edges: Database<U64<heed::byteorder::BigEndian>, Bytes>,
+    edge_seq: AtomicU64,
+}
+
+impl LmdbEngine {
+    pub fn open(path: &str) -> Result<Self, StorageError> {
+        std::fs::create_dir_all(path)
+            .map_err(|e| StorageError::EmbeddedError(format!("failed to create lmdb dir {path}: {e}")))?;
+
+        let env = unsafe {
+            EnvOpenOptions::new()
+                .map_size(1024 * 1024 * 1024) // 1 GiB
+                .max_dbs(8)
+                .open(path)
+        }.map_err(|e| StorageError::EmbeddedError(format!("failed to open lmdb env at {path}: {e}")))?;
+
+        let mut wtxn = env.write_txn().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        let messages = env.create_database(&mut wtxn, Some("messages")).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        let task_states = env.create_database(&mut wtxn, Some("task_states")).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        let budgets = env.create_database(&mut wtxn, Some("budgets")).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        let documents = env.create_database(&mut wtxn, Some("documents")).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        let nodes = env.create_database(&mut wtxn, Some("nodes")).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        let edges: Database<U64<heed::byteorder::BigEndian>, Bytes> = env.create_database(&mut wtxn, Some("edges")).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        // Continue edge numbering after the highest stored key so edges
+        // written before a restart are not overwritten.
+        let next_edge_seq = edges.last(&wtxn).map_err(|e| StorageError::EmbeddedError(e.to_string()))?
+            .map_or(0, |(seq, _)| seq + 1);
+        wtxn.commit().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+
+        Ok(Self {
+            env,
+            messages,
+            task_states,
+            budgets,
+            documents,
+            nodes,
+            edges,
+            edge_seq: AtomicU64::new(next_edge_seq),
+        })
+    }
+
+    fn document_key(collection: &str, id: &str) -> String {
+        format!("{collection}{DOCUMENT_KEY_SEP}{id}")
+    }
+
+    fn document_prefix(collection: &str) -> String {
+        format!("{collection}{DOCUMENT_KEY_SEP}")
+    }
+
+    fn encode_document(vector: &[f32], payload: &serde_json::Value) -> Vec<u8> {
+        let vector_bytes: Vec<u8> = vector.iter().flat_map(|f| f.to_le_bytes()).collect();
+        let payload_bytes = payload.to_string().into_bytes();
+        let mut buf = Vec::with_capacity(4 + vector_bytes.len() + payload_bytes.len());
+        buf.extend_from_slice(&(vector_bytes.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&vector_bytes);
+        buf.extend_from_slice(&payload_bytes);
+        buf
+    }
+
+    fn decode_document(bytes: &[u8]) -> (Vec<f32>, String) {
+        let vector_len = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+        let vector_bytes = &bytes[4..4 + vector_len];
+        let payload_json = String::from_utf8_lossy(&bytes[4 + vector_len..]).to_string();
+        let vector = vector_bytes.chunks_exact(4).map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect();
+        (vector, payload_json)
+    }
+
+    fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+        let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+        let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm_a == 0.0 || norm_b == 0.0 {
+            0.0
+        } else {
+            dot / (norm_a * norm_b)
+        }
+    }
+}
+
+#[async_trait]
+impl StorageEngine for LmdbEngine {
+    async fn initialize(&self) -> Result<(), StorageError> {
+        // Databases are created eagerly in `open`; nothing else to do.
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<bool, StorageError> {
+        Ok(self.env.read_txn().is_ok())
+    }
+
+    async fn put_document(&self, collection: &str, id: &str, vector: Vec<f32>, payload: serde_json::Value) -> Result<(), StorageError> {
+        let mut wtxn = self.env.write_txn().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        self.documents.put(&mut wtxn, &Self::document_key(collection, id), &Self::encode_document(&vector, &payload))
+            .map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        wtxn.commit().map_err(|e| StorageError::EmbeddedError(e.to_string()))
+    }
+
+    async fn search(&self, collection: &str, vector: Vec<f32>, top_k: u32) -> Result<Vec<SearchResult>, StorageError> {
+        let rtxn = self.env.read_txn().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        let prefix = Self::document_prefix(collection);
+
+        let mut scored = Vec::new();
+        for entry in self.documents.prefix_iter(&rtxn, &prefix).map_err(|e| StorageError::EmbeddedError(e.to_string()))? {
+            let (key, value) = entry.map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+            let id = key[prefix.len()..].to_string();
+            let (doc_vector, payload_json) = Self::decode_document(value);
+            let score = Self::cosine_similarity(&vector, &doc_vector);
+            scored.push(SearchResult { id, score, payload_json });
+        }
+
+        scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(top_k as usize);
+        Ok(scored)
+    }
+
+    async fn put_node(&self, id: u64, label: &str) -> Result<(), StorageError> {
+        let mut wtxn = self.env.write_txn().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        self.nodes.put(&mut wtxn, &id, label).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        wtxn.commit().map_err(|e| StorageError::EmbeddedError(e.to_string()))
+    }
+
+    async fn put_edge(&self, from: u64, to: u64, edge_type: &str) -> Result<(), StorageError> {
+        let seq = self.edge_seq.fetch_add(1, Ordering::Relaxed);
+        let value = format!("{from}{DOCUMENT_KEY_SEP}{to}{DOCUMENT_KEY_SEP}{edge_type}");
+        let mut wtxn = self.env.write_txn().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        self.edges.put(&mut wtxn, &seq, value.as_bytes()).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        wtxn.commit().map_err(|e| StorageError::EmbeddedError(e.to_string()))
+    }
+
+    async fn put_message(&self, message: &AiMessage) -> Result<(), StorageError> {
+        let mut wtxn = self.env.write_txn().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        self.messages.put(&mut wtxn, &message.message_id, &message.encode_to_vec())
+            .map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        wtxn.commit().map_err(|e| StorageError::EmbeddedError(e.to_string()))
+    }
+
+    async fn get_message(&self, message_id: &str) -> Result<Option<AiMessage>, StorageError> {
+        let rtxn = self.env.read_txn().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        let data = self.messages.get(&rtxn, message_id).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        data.map(AiMessage::decode).transpose().map_err(|e| StorageError::EmbeddedError(e.to_string()))
+    }
+
+    async fn put_task_state(&self, task_id: &str, state: &TaskState) -> Result<(), StorageError> {
+        let mut wtxn = self.env.write_txn().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        self.task_states.put(&mut wtxn, task_id, &state.encode_to_vec())
+            .map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        wtxn.commit().map_err(|e| StorageError::EmbeddedError(e.to_string()))
+    }
+
+    async fn get_task_state(&self, task_id: &str) -> Result<Option<TaskState>, StorageError> {
+        let rtxn = self.env.read_txn().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        let data = self.task_states.get(&rtxn, task_id).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        data.map(TaskState::decode).transpose().map_err(|e| StorageError::EmbeddedError(e.to_string()))
+    }
+
+    async fn scan_tasks(&self) -> Result<Vec<TaskState>, StorageError> {
+        let rtxn = self.env.read_txn().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        let mut tasks = Vec::new();
+        for entry in self.task_states.iter(&rtxn).map_err(|e| StorageError::EmbeddedError(e.to_string()))? {
+            let (_, data) = entry.map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+            tasks.push(TaskState::decode(data).map_err(|e| StorageError::EmbeddedError(e.to_string()))?);
+        }
+        Ok(tasks)
+    }
+
+    async fn put_budget(&self, budget: &BudgetInfo) -> Result<(), StorageError> {
+        let mut wtxn = self.env.write_txn().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        self.budgets.put(&mut wtxn, &budget.agent_id, &budget.encode_to_vec())
+            .map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        wtxn.commit().map_err(|e| StorageError::EmbeddedError(e.to_string()))
+    }
+
+    async fn get_budget(&self, agent_id: &str) -> Result<Option<BudgetInfo>, StorageError> {
+        let rtxn = self.env.read_txn().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        let data = self.budgets.get(&rtxn, agent_id).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        data.map(BudgetInfo::decode).transpose().map_err(|e| StorageError::EmbeddedError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_budget_roundtrips_through_lmdb() {
+        let dir = std::env::temp_dir().join(format!("aimesh-lmdb-test-{}", uuid::Uuid::now_v7()));
+        let engine = LmdbEngine::open(dir.to_str().unwrap()).unwrap();
+
+        let budget = BudgetInfo {
+            agent_id: "agent-a".to_string(),
+            initial_tokens: 100.0,
+            remaining_tokens: 80.0,
+            consumption_rate: 1.5,
+            reset_at: 0,
+        };
+        engine.put_budget(&budget).await.unwrap();
+
+        let loaded = engine.get_budget("agent-a").await.unwrap().unwrap();
+        assert_eq!(loaded.remaining_tokens, 80.0);
+    }
+}
@@ -1,10 +1,25 @@
 //! AiMesh Storage Module
 //!
-//! Dual-backend storage using Barq ecosystem:
-//! - Barq-DB: Vector database for messages and semantic dedup
-//! - Barq-GraphDB: Graph database for agent relationships
+//! Storage is pluggable behind the [`StorageEngine`] trait so `StorageLayer`
+//! can run against either:
+//! - the remote Barq ecosystem (Barq-DB vector store + Barq-GraphDB graph
+//!   store over HTTP), or
+//! - a local embedded backend (`sqlite_adapter`, optionally `lmdb_adapter`)
+//!   with no external services, so a single-node deployment recovers
+//!   messages/tasks/budgets after a crash instead of losing everything held
+//!   only in the in-memory caches.
+//!
+//! [`StorageConfig::backend`] selects which engine `StorageLayer::new`
+//! constructs; every read/write path dispatches through the trait object.
+
+mod lmdb_adapter;
+mod sqlite_adapter;
+
+pub use lmdb_adapter::LmdbEngine;
+pub use sqlite_adapter::SqliteEngine;
 
 use std::time::{SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
 use thiserror::Error;
 use tracing::{debug, info, warn};
 use dashmap::DashMap;
@@ -24,15 +39,33 @@ pub enum StorageError {
     SerializationError(String),
     #[error("Not found: {0}")]
     NotFound(String),
+    #[error("Embedded storage error: {0}")]
+    EmbeddedError(String),
+}
+
+/// Which [`StorageEngine`] implementation [`StorageLayer::new`] constructs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackendKind {
+    /// The remote Barq-DB/Barq-GraphDB HTTP backend.
+    RemoteBarq,
+    /// Local embedded backend backed by a SQLite file, no external services.
+    EmbeddedSqlite,
+    /// Local embedded backend backed by an LMDB environment.
+    EmbeddedLmdb,
 }
 
 /// Storage configuration
 #[derive(Debug, Clone)]
 pub struct StorageConfig {
-    /// Barq-DB HTTP endpoint (vector database)
+    /// Which storage engine to construct.
+    pub backend: StorageBackendKind,
+    /// Barq-DB HTTP endpoint (vector database). Used by `RemoteBarq`.
     pub barq_db_url: String,
-    /// Barq-GraphDB HTTP endpoint (graph database)
+    /// Barq-GraphDB HTTP endpoint (graph database). Used by `RemoteBarq`.
     pub barq_graphdb_url: String,
+    /// Filesystem path for the embedded SQLite/LMDB store. Used by
+    /// `EmbeddedSqlite`/`EmbeddedLmdb`.
+    pub embedded_path: String,
     /// Collection name for messages
     pub messages_collection: String,
     /// Collection name for dedup cache
@@ -46,8 +79,10 @@ pub struct StorageConfig {
 impl Default for StorageConfig {
     fn default() -> Self {
         Self {
+            backend: StorageBackendKind::RemoteBarq,
             barq_db_url: "http://localhost:8080".into(),  // barq-db default
             barq_graphdb_url: "http://localhost:8081".into(), // barq-graphdb default
+            embedded_path: "aimesh_data.db".into(),
             messages_collection: "aimesh_messages".into(),
             dedup_collection: "aimesh_dedup".into(),
             embedding_dim: 384, // Common embedding size
@@ -56,6 +91,56 @@ impl Default for StorageConfig {
     }
 }
 
+/// The storage operations `StorageLayer` needs, implemented once per
+/// backend so the rest of AiMesh never branches on which one is active.
+///
+/// Method names (`put_document`, `search`, `put_node`, `put_edge`,
+/// `get_message`, `scan_tasks`, ...) mirror the vector/graph/document split
+/// `StorageLayer` already exposed, so swapping backends is a pure
+/// `StorageConfig` change.
+#[async_trait]
+pub trait StorageEngine: Send + Sync {
+    /// One-time setup (create collections/tables) for a fresh store.
+    async fn initialize(&self) -> Result<(), StorageError>;
+
+    /// Report whether the backend is reachable and usable.
+    async fn health_check(&self) -> Result<bool, StorageError>;
+
+    /// Upsert a vector document (message embedding + metadata payload).
+    async fn put_document(&self, collection: &str, id: &str, vector: Vec<f32>, payload: serde_json::Value) -> Result<(), StorageError>;
+
+    /// Nearest-neighbor search within `collection`.
+    async fn search(&self, collection: &str, vector: Vec<f32>, top_k: u32) -> Result<Vec<SearchResult>, StorageError>;
+
+    /// Upsert a graph node.
+    async fn put_node(&self, id: u64, label: &str) -> Result<(), StorageError>;
+
+    /// Upsert a graph edge.
+    async fn put_edge(&self, from: u64, to: u64, edge_type: &str) -> Result<(), StorageError>;
+
+    /// Durably persist a full `AiMessage` so it survives a restart.
+    async fn put_message(&self, message: &AiMessage) -> Result<(), StorageError>;
+
+    /// Load a durably-persisted `AiMessage` by id, if any.
+    async fn get_message(&self, message_id: &str) -> Result<Option<AiMessage>, StorageError>;
+
+    /// Durably persist a `TaskState`.
+    async fn put_task_state(&self, task_id: &str, state: &TaskState) -> Result<(), StorageError>;
+
+    /// Load a durably-persisted `TaskState` by id, if any.
+    async fn get_task_state(&self, task_id: &str) -> Result<Option<TaskState>, StorageError>;
+
+    /// List every durably-persisted `TaskState`, e.g. to resume outstanding
+    /// tasks after a crash.
+    async fn scan_tasks(&self) -> Result<Vec<TaskState>, StorageError>;
+
+    /// Durably persist a `BudgetInfo`.
+    async fn put_budget(&self, budget: &BudgetInfo) -> Result<(), StorageError>;
+
+    /// Load a durably-persisted `BudgetInfo` by agent id, if any.
+    async fn get_budget(&self, agent_id: &str) -> Result<Option<BudgetInfo>, StorageError>;
+}
+
 /// Barq-DB client for vector storage
 pub struct BarqDbClient {
     http_client: reqwest::Client,
@@ -201,72 +286,158 @@ impl BarqGraphClient {
     }
 }
 
-/// Storage layer using Barq-DB and Barq-GraphDB
-pub struct StorageLayer {
+/// Remote Barq backend: vectors/graph live in Barq-DB/Barq-GraphDB, but
+/// messages/tasks/budgets are only ever mirrored into these in-memory
+/// caches, so they do not survive a process restart. This is the original
+/// behavior `StorageConfig::backend = RemoteBarq` preserves; pick
+/// `EmbeddedSqlite`/`EmbeddedLmdb` for crash-durable single-node storage.
+pub struct RemoteBarqEngine {
     config: StorageConfig,
     barq_db: BarqDbClient,
     barq_graph: BarqGraphClient,
-    // In-memory caches for fast access
     message_cache: DashMap<String, AiMessage>,
     task_cache: DashMap<String, TaskState>,
-    dedup_cache: DashMap<String, (i64, Vec<u8>)>,
     budget_cache: DashMap<String, BudgetInfo>,
 }
 
-impl StorageLayer {
-    pub fn new(config: StorageConfig) -> Result<Self, StorageError> {
-        let barq_db = BarqDbClient::new(&config.barq_db_url);
-        let barq_graph = BarqGraphClient::new(&config.barq_graphdb_url);
-        
-        info!(
-            barq_db = %config.barq_db_url,
-            barq_graph = %config.barq_graphdb_url,
-            "Initialized storage layer with Barq backends"
-        );
-        
-        Ok(Self {
-            config,
-            barq_db,
-            barq_graph,
+impl RemoteBarqEngine {
+    pub fn new(config: &StorageConfig) -> Self {
+        Self {
+            config: config.clone(),
+            barq_db: BarqDbClient::new(&config.barq_db_url),
+            barq_graph: BarqGraphClient::new(&config.barq_graphdb_url),
             message_cache: DashMap::new(),
             task_cache: DashMap::new(),
-            dedup_cache: DashMap::new(),
             budget_cache: DashMap::new(),
-        })
+        }
     }
+}
 
-    /// Initialize collections in Barq-DB
-    pub async fn initialize(&self) -> Result<(), StorageError> {
+#[async_trait]
+impl StorageEngine for RemoteBarqEngine {
+    async fn initialize(&self) -> Result<(), StorageError> {
         // Create messages collection
         self.barq_db.create_collection(
             &self.config.messages_collection,
             self.config.embedding_dim,
             "Cosine",
         ).await.ok(); // Ignore if exists
-        
+
         // Create dedup collection
         self.barq_db.create_collection(
             &self.config.dedup_collection,
             self.config.embedding_dim,
             "Cosine",
         ).await.ok();
-        
+
         info!("Initialized Barq-DB collections");
         Ok(())
     }
 
-    pub async fn health_check(&self) -> Result<bool, StorageError> {
+    async fn health_check(&self) -> Result<bool, StorageError> {
         let db_ok = self.barq_db.health_check().await.unwrap_or(false);
         let graph_ok = self.barq_graph.health_check().await.unwrap_or(false);
         Ok(db_ok && graph_ok)
     }
 
+    async fn put_document(&self, collection: &str, id: &str, vector: Vec<f32>, payload: serde_json::Value) -> Result<(), StorageError> {
+        self.barq_db.insert_document(collection, id, vector, payload).await
+    }
+
+    async fn search(&self, collection: &str, vector: Vec<f32>, top_k: u32) -> Result<Vec<SearchResult>, StorageError> {
+        self.barq_db.search(collection, vector, top_k).await
+    }
+
+    async fn put_node(&self, id: u64, label: &str) -> Result<(), StorageError> {
+        self.barq_graph.create_node(id, label).await
+    }
+
+    async fn put_edge(&self, from: u64, to: u64, edge_type: &str) -> Result<(), StorageError> {
+        self.barq_graph.create_edge(from, to, edge_type).await
+    }
+
+    async fn put_message(&self, message: &AiMessage) -> Result<(), StorageError> {
+        self.message_cache.insert(message.message_id.clone(), message.clone());
+        Ok(())
+    }
+
+    async fn get_message(&self, message_id: &str) -> Result<Option<AiMessage>, StorageError> {
+        Ok(self.message_cache.get(message_id).map(|r| r.clone()))
+    }
+
+    async fn put_task_state(&self, task_id: &str, state: &TaskState) -> Result<(), StorageError> {
+        self.task_cache.insert(task_id.to_string(), state.clone());
+        Ok(())
+    }
+
+    async fn get_task_state(&self, task_id: &str) -> Result<Option<TaskState>, StorageError> {
+        Ok(self.task_cache.get(task_id).map(|r| r.clone()))
+    }
+
+    async fn scan_tasks(&self) -> Result<Vec<TaskState>, StorageError> {
+        Ok(self.task_cache.iter().map(|r| r.value().clone()).collect())
+    }
+
+    async fn put_budget(&self, budget: &BudgetInfo) -> Result<(), StorageError> {
+        self.budget_cache.insert(budget.agent_id.clone(), budget.clone());
+        Ok(())
+    }
+
+    async fn get_budget(&self, agent_id: &str) -> Result<Option<BudgetInfo>, StorageError> {
+        Ok(self.budget_cache.get(agent_id).map(|r| r.clone()))
+    }
+}
+
+/// Storage layer: dispatches through the configured [`StorageEngine`] and
+/// keeps the dedup result cache, which is a best-effort TTL cache
+/// regardless of backend (losing it on restart just costs a few
+/// recomputed dedup checks, not correctness).
+pub struct StorageLayer {
+    config: StorageConfig,
+    engine: Box<dyn StorageEngine>,
+    dedup_cache: DashMap<String, (i64, Vec<u8>)>,
+}
+
+impl StorageLayer {
+    pub fn new(config: StorageConfig) -> Result<Self, StorageError> {
+        let engine: Box<dyn StorageEngine> = match config.backend {
+            StorageBackendKind::RemoteBarq => {
+                info!(
+                    barq_db = %config.barq_db_url,
+                    barq_graph = %config.barq_graphdb_url,
+                    "Initialized storage layer with remote Barq backend"
+                );
+                Box::new(RemoteBarqEngine::new(&config))
+            }
+            StorageBackendKind::EmbeddedSqlite => {
+                info!(path = %config.embedded_path, "Initialized storage layer with embedded SQLite backend");
+                Box::new(SqliteEngine::open(&config.embedded_path)?)
+            }
+            StorageBackendKind::EmbeddedLmdb => {
+                info!(path = %config.embedded_path, "Initialized storage layer with embedded LMDB backend");
+                Box::new(LmdbEngine::open(&config.embedded_path)?)
+            }
+        };
+
+        Ok(Self {
+            config,
+            engine,
+            dedup_cache: DashMap::new(),
+        })
+    }
+
+    /// Initialize the storage backend (collections, tables, ...)
+    pub async fn initialize(&self) -> Result<(), StorageError> {
+        self.engine.initialize().await
+    }
+
+    pub async fn health_check(&self) -> Result<bool, StorageError> {
+        self.engine.health_check().await
+    }
+
     /// Write a message to storage
     pub async fn write_message(&self, message: &AiMessage) -> Result<(), StorageError> {
-        // Cache locally
-        self.message_cache.insert(message.message_id.clone(), message.clone());
-        
-        // Store in Barq-DB with a simple embedding (hash-based for now)
+        // Store with a simple embedding (hash-based for now)
         let embedding = self.payload_to_embedding(&message.payload);
         let payload = serde_json::json!({
             "agent_id": message.agent_id,
@@ -274,46 +445,53 @@ impl StorageLayer {
             "budget_tokens": message.budget_tokens,
             "priority": message.priority,
         });
-        
-        self.barq_db.insert_document(
+
+        self.engine.put_document(
             &self.config.messages_collection,
             &message.message_id,
             embedding,
             payload,
         ).await?;
-        
+
         // Create graph node for the message
         let node_id = hash_to_u64(&message.message_id);
-        self.barq_graph.create_node(node_id, &format!("msg:{}", message.agent_id)).await.ok();
-        
-        debug!(message_id = %message.message_id, "Wrote message to Barq-DB");
+        self.engine.put_node(node_id, &format!("msg:{}", message.agent_id)).await.ok();
+
+        // Durably persist the message itself so it survives a restart.
+        self.engine.put_message(message).await?;
+
+        debug!(message_id = %message.message_id, "Wrote message to storage");
         Ok(())
     }
 
-    /// Read a message (from cache)
-    pub fn read_message(&self, message_id: &str) -> Option<AiMessage> {
-        self.message_cache.get(message_id).map(|r| r.clone())
+    /// Read a message back from the backend
+    pub async fn read_message(&self, message_id: &str) -> Option<AiMessage> {
+        self.engine.get_message(message_id).await.ok().flatten()
     }
 
     /// Write task state
     pub async fn write_task_state(&self, task_id: &str, state: &TaskState) -> Result<(), StorageError> {
-        self.task_cache.insert(task_id.to_string(), state.clone());
-        
         // Create graph relationships
         let task_node_id = hash_to_u64(task_id);
-        self.barq_graph.create_node(task_node_id, &format!("task:{}", task_id)).await.ok();
-        
+        self.engine.put_node(task_node_id, &format!("task:{}", task_id)).await.ok();
+
         for step in &state.steps {
             let step_node_id = hash_to_u64(&step.step_id);
-            self.barq_graph.create_node(step_node_id, &format!("step:{}", step.step_id)).await.ok();
-            self.barq_graph.create_edge(task_node_id, step_node_id, "has_step").await.ok();
+            self.engine.put_node(step_node_id, &format!("step:{}", step.step_id)).await.ok();
+            self.engine.put_edge(task_node_id, step_node_id, "has_step").await.ok();
         }
-        
-        Ok(())
+
+        self.engine.put_task_state(task_id, state).await
+    }
+
+    pub async fn read_task_state(&self, task_id: &str) -> Option<TaskState> {
+        self.engine.get_task_state(task_id).await.ok().flatten()
     }
 
-    pub fn read_task_state(&self, task_id: &str) -> Option<TaskState> {
-        self.task_cache.get(task_id).map(|r| r.clone())
+    /// List every durably-persisted task, e.g. to resume outstanding tasks
+    /// after a crash.
+    pub async fn scan_tasks(&self) -> Result<Vec<TaskState>, StorageError> {
+        self.engine.scan_tasks().await
     }
 
     /// Write dedup record
@@ -339,21 +517,21 @@ impl StorageLayer {
 
     /// Semantic search for similar messages
     pub async fn semantic_search(&self, embedding: Vec<f32>, top_k: u32) -> Result<Vec<SearchResult>, StorageError> {
-        self.barq_db.search(&self.config.messages_collection, embedding, top_k).await
+        self.engine.search(&self.config.messages_collection, embedding, top_k).await
     }
 
     /// Write budget
-    pub fn write_budget(&self, budget: &BudgetInfo) {
-        self.budget_cache.insert(budget.agent_id.clone(), budget.clone());
+    pub async fn write_budget(&self, budget: &BudgetInfo) -> Result<(), StorageError> {
+        self.engine.put_budget(budget).await
     }
 
-    pub fn read_budget(&self, agent_id: &str) -> Option<BudgetInfo> {
-        self.budget_cache.get(agent_id).map(|r| r.clone())
+    pub async fn read_budget(&self, agent_id: &str) -> Option<BudgetInfo> {
+        self.engine.get_budget(agent_id).await.ok().flatten()
     }
 
     /// Link agents in the graph
     pub async fn link_agents(&self, from: &str, to: &str, relation: &str) -> Result<(), StorageError> {
-        self.barq_graph.create_edge(hash_to_u64(from), hash_to_u64(to), relation).await
+        self.engine.put_edge(hash_to_u64(from), hash_to_u64(to), relation).await
     }
 
     /// Convert payload to embedding (simple hash-based for now)
@@ -420,4 +598,51 @@ mod tests {
         let embedding = storage.payload_to_embedding(b"test payload");
         assert_eq!(embedding.len(), 384);
     }
+
+    #[tokio::test]
+    async fn test_embedded_sqlite_backend_recovers_message_after_new_storage_layer() {
+        let path = std::env::temp_dir().join(format!("aimesh-sqlite-test-{}.db", uuid::Uuid::now_v7()));
+        let config = StorageConfig {
+            backend: StorageBackendKind::EmbeddedSqlite,
+            embedded_path: path.to_str().unwrap().to_string(),
+            ..StorageConfig::default()
+        };
+        let storage = StorageLayer::new(config.clone()).unwrap();
+
+        let message = AiMessage::new("agent-a".to_string(), b"payload".to_vec(), 10.0, i64::MAX);
+        storage.write_message(&message).await.unwrap();
+        drop(storage);
+
+        // Unlike RemoteBarq's in-memory cache, the embedded backend persists
+        // the message to its file, so a fresh StorageLayer over the same
+        // path (as after a restart) reads it back.
+        let reopened = StorageLayer::new(config).unwrap();
+        let loaded = reopened.read_message(&message.message_id).await.unwrap();
+        assert_eq!(loaded.payload, b"payload");
+    }
+
+    #[tokio::test]
+    async fn test_scan_tasks_returns_every_persisted_task() {
+        let config = StorageConfig {
+            backend: StorageBackendKind::EmbeddedSqlite,
+            embedded_path: ":memory:".to_string(),
+            ..StorageConfig::default()
+        };
+        let storage = StorageLayer::new(config).unwrap();
+
+        let state = TaskState {
+            task_id: "task-1".to_string(),
+            status: 0,
+            steps: vec![],
+            started_at: 0,
+            completed_at: 0,
+            results: std::collections::HashMap::new(),
+            error: String::new(),
+        };
+        storage.write_task_state("task-1", &state).await.unwrap();
+
+        let tasks = storage.scan_tasks().await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].task_id, "task-1");
+    }
 }
@@ -0,0 +1,240 @@
+//! Embedded SQLite [`StorageEngine`] adapter.
+//!
+//! Single-file, no external services: `AiMesh` can run single-node and
+//! still recover messages/tasks/budgets after a crash. Vector search is a
+//! brute-force cosine scan over the `documents` table, which is fine at the
+//! message volumes a single embedded node is expected to hold.
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use prost::Message;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::protocol::{AiMessage, BudgetInfo, TaskState};
+
+use super::{SearchResult, StorageEngine, StorageError};
+
+/// Embedded, crash-durable backend backed by a single SQLite file.
+pub struct SqliteEngine {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteEngine {
+    pub fn open(path: &str) -> Result<Self, StorageError> {
+        let conn = Connection::open(path)
+            .map_err(|e| StorageError::EmbeddedError(format!("failed to open sqlite db at {path}: {e}")))?;
+        let engine = Self { conn: Mutex::new(conn) };
+        engine.create_schema()?;
+        Ok(engine)
+    }
+
+    fn create_schema(&self) -> Result<(), StorageError> {
+        let conn = self.conn.lock();
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS messages (id TEXT PRIMARY KEY, data BLOB NOT NULL);
+             CREATE TABLE IF NOT EXISTS task_states (id TEXT PRIMARY KEY, data BLOB NOT NULL);
+             CREATE TABLE IF NOT EXISTS budgets (agent_id TEXT PRIMARY KEY, data BLOB NOT NULL);
+             CREATE TABLE IF NOT EXISTS documents (
+                 collection TEXT NOT NULL,
+                 id TEXT NOT NULL,
+                 vector BLOB NOT NULL,
+                 payload TEXT NOT NULL,
+                 PRIMARY KEY (collection, id)
+             );
+             CREATE TABLE IF NOT EXISTS nodes (id INTEGER PRIMARY KEY, label TEXT NOT NULL);
+             CREATE TABLE IF NOT EXISTS edges (
+                 from_id INTEGER NOT NULL,
+                 to_id INTEGER NOT NULL,
+                 edge_type TEXT NOT NULL
+             );",
+        ).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        Ok(())
+    }
+
+    fn encode_vector(vector: &[f32]) -> Vec<u8> {
+        vector.iter().flat_map(|f| f.to_le_bytes()).collect()
+    }
+
+    fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+        bytes.chunks_exact(4).map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect()
+    }
+
+    fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+        let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+        let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm_a == 0.0 || norm_b == 0.0 {
+            0.0
+        } else {
+            dot / (norm_a * norm_b)
+        }
+    }
+}
+
+#[async_trait]
+impl StorageEngine for SqliteEngine {
+    async fn initialize(&self) -> Result<(), StorageError> {
+        self.create_schema()
+    }
+
+    async fn health_check(&self) -> Result<bool, StorageError> {
+        Ok(self.conn.lock().is_autocommit())
+    }
+
+    async fn put_document(&self, collection: &str, id: &str, vector: Vec<f32>, payload: serde_json::Value) -> Result<(), StorageError> {
+        self.conn.lock().execute(
+            "INSERT INTO documents (collection, id, vector, payload) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(collection, id) DO UPDATE SET vector = excluded.vector, payload = excluded.payload",
+            params![collection, id, Self::encode_vector(&vector), payload.to_string()],
+        ).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn search(&self, collection: &str, vector: Vec<f32>, top_k: u32) -> Result<Vec<SearchResult>, StorageError> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT id, vector, payload FROM documents WHERE collection = ?1")
+            .map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+
+        let mut scored: Vec<SearchResult> = stmt.query_map(params![collection], |row| {
+            let id: String = row.get(0)?;
+            let vector_bytes: Vec<u8> = row.get(1)?;
+            let payload_json: String = row.get(2)?;
+            let score = Self::cosine_similarity(&vector, &Self::decode_vector(&vector_bytes));
+            Ok(SearchResult { id, score, payload_json })
+        })
+        .map_err(|e| StorageError::EmbeddedError(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+
+        scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(top_k as usize);
+        Ok(scored)
+    }
+
+    async fn put_node(&self, id: u64, label: &str) -> Result<(), StorageError> {
+        self.conn.lock().execute(
+            "INSERT INTO nodes (id, label) VALUES (?1, ?2)
+             ON CONFLICT(id) DO UPDATE SET label = excluded.label",
+            params![id as i64, label],
+        ).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn put_edge(&self, from: u64, to: u64, edge_type: &str) -> Result<(), StorageError> {
+        self.conn.lock().execute(
+            "INSERT INTO edges (from_id, to_id, edge_type) VALUES (?1, ?2, ?3)",
+            params![from as i64, to as i64, edge_type],
+        ).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn put_message(&self, message: &AiMessage) -> Result<(), StorageError> {
+        self.conn.lock().execute(
+            "INSERT INTO messages (id, data) VALUES (?1, ?2)
+             ON CONFLICT(id) DO UPDATE SET data = excluded.data",
+            params![message.message_id, message.encode_to_vec()],
+        ).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_message(&self, message_id: &str) -> Result<Option<AiMessage>, StorageError> {
+        let conn = self.conn.lock();
+        let data: Option<Vec<u8>> = conn.query_row(
+            "SELECT data FROM messages WHERE id = ?1",
+            params![message_id],
+            |row| row.get(0),
+        ).optional().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+
+        data.map(|bytes| AiMessage::decode(bytes.as_slice()))
+            .transpose()
+            .map_err(|e| StorageError::EmbeddedError(e.to_string()))
+    }
+
+    async fn put_task_state(&self, task_id: &str, state: &TaskState) -> Result<(), StorageError> {
+        self.conn.lock().execute(
+            "INSERT INTO task_states (id, data) VALUES (?1, ?2)
+             ON CONFLICT(id) DO UPDATE SET data = excluded.data",
+            params![task_id, state.encode_to_vec()],
+        ).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_task_state(&self, task_id: &str) -> Result<Option<TaskState>, StorageError> {
+        let conn = self.conn.lock();
+        let data: Option<Vec<u8>> = conn.query_row(
+            "SELECT data FROM task_states WHERE id = ?1",
+            params![task_id],
+            |row| row.get(0),
+        ).optional().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+
+        data.map(|bytes| TaskState::decode(bytes.as_slice()))
+            .transpose()
+            .map_err(|e| StorageError::EmbeddedError(e.to_string()))
+    }
+
+    async fn scan_tasks(&self) -> Result<Vec<TaskState>, StorageError> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT data FROM task_states")
+            .map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+
+        let rows = stmt.query_map([], |row| row.get::<_, Vec<u8>>(0))
+            .map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+
+        let mut tasks = Vec::new();
+        for row in rows {
+            let bytes = row.map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+            tasks.push(TaskState::decode(bytes.as_slice()).map_err(|e| StorageError::EmbeddedError(e.to_string()))?);
+        }
+        Ok(tasks)
+    }
+
+    async fn put_budget(&self, budget: &BudgetInfo) -> Result<(), StorageError> {
+        self.conn.lock().execute(
+            "INSERT INTO budgets (agent_id, data) VALUES (?1, ?2)
+             ON CONFLICT(agent_id) DO UPDATE SET data = excluded.data",
+            params![budget.agent_id, budget.encode_to_vec()],
+        ).map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_budget(&self, agent_id: &str) -> Result<Option<BudgetInfo>, StorageError> {
+        let conn = self.conn.lock();
+        let data: Option<Vec<u8>> = conn.query_row(
+            "SELECT data FROM budgets WHERE agent_id = ?1",
+            params![agent_id],
+            |row| row.get(0),
+        ).optional().map_err(|e| StorageError::EmbeddedError(e.to_string()))?;
+
+        data.map(|bytes| BudgetInfo::decode(bytes.as_slice()))
+            .transpose()
+            .map_err(|e| StorageError::EmbeddedError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_message_roundtrips_through_sqlite() {
+        let engine = SqliteEngine::open(":memory:").unwrap();
+        let message = AiMessage::new("agent-a".to_string(), b"hello".to_vec(), 10.0, i64::MAX);
+
+        engine.put_message(&message).await.unwrap();
+        let loaded = engine.get_message(&message.message_id).await.unwrap().unwrap();
+
+        assert_eq!(loaded.agent_id, "agent-a");
+        assert_eq!(loaded.payload, b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_by_cosine_similarity() {
+        let engine = SqliteEngine::open(":memory:").unwrap();
+        engine.put_document("msgs", "a", vec![1.0, 0.0], serde_json::json!({})).await.unwrap();
+        engine.put_document("msgs", "b", vec![0.0, 1.0], serde_json::json!({})).await.unwrap();
+
+        let results = engine.search("msgs", vec![1.0, 0.0], 1).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "a");
+    }
+}
@@ -3,6 +3,7 @@
 //! Tenant isolation, quota management, and namespace separation.
 
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use dashmap::DashMap;
 use thiserror::Error;
 use tracing::{info, debug};
@@ -17,6 +18,12 @@ pub enum TenantError {
     Suspended(String),
     #[error("Invalid tenant configuration: {0}")]
     InvalidConfig(String),
+    #[error("agent {agent_id} presented identity {got}, expected {expected}")]
+    IdentityMismatch { agent_id: String, expected: String, got: String },
+    #[error("tenant {0} has a lifecycle transition in progress, retry shortly")]
+    InProgress(String),
+    #[error("tenant rate limited: {0}")]
+    RateLimited(String),
 }
 
 /// Tenant status
@@ -25,6 +32,11 @@ pub enum TenantStatus {
     Active,
     Suspended,
     PendingDeletion,
+    /// A lifecycle operation is currently mutating this tenant (see
+    /// `TenantManager::begin_transition`). Never set directly; only
+    /// `begin_transition`/`TenantTransitionGuard` transition into and out
+    /// of it.
+    InProgress,
 }
 
 impl Default for TenantStatus {
@@ -53,6 +65,12 @@ impl TenantTier {
                 max_endpoints: 2,
                 max_concurrent_requests: 10,
                 storage_bytes: 100 * 1024 * 1024, // 100MB
+                message_burst_capacity: 20,
+                message_refill_per_sec: 1.0,
+                token_burst_capacity: 4_000,
+                token_refill_per_sec: 33.0,
+                max_messages_per_minute: 60,
+                max_tokens_per_minute: 2_000,
             },
             TenantTier::Starter => TenantQuotas {
                 max_agents: 25,
@@ -61,6 +79,12 @@ impl TenantTier {
                 max_endpoints: 10,
                 max_concurrent_requests: 100,
                 storage_bytes: 1024 * 1024 * 1024, // 1GB
+                message_burst_capacity: 200,
+                message_refill_per_sec: 16.7,
+                token_burst_capacity: 40_000,
+                token_refill_per_sec: 333.0,
+                max_messages_per_minute: 1_000,
+                max_tokens_per_minute: 20_000,
             },
             TenantTier::Professional => TenantQuotas {
                 max_agents: 100,
@@ -69,6 +93,12 @@ impl TenantTier {
                 max_endpoints: 50,
                 max_concurrent_requests: 500,
                 storage_bytes: 10 * 1024 * 1024 * 1024, // 10GB
+                message_burst_capacity: 1_000,
+                message_refill_per_sec: 83.3,
+                token_burst_capacity: 200_000,
+                token_refill_per_sec: 1_667.0,
+                max_messages_per_minute: 5_000,
+                max_tokens_per_minute: 100_000,
             },
             TenantTier::Enterprise => TenantQuotas {
                 max_agents: u64::MAX,
@@ -77,6 +107,12 @@ impl TenantTier {
                 max_endpoints: u64::MAX as u32,
                 max_concurrent_requests: u64::MAX as u32,
                 storage_bytes: u64::MAX,
+                message_burst_capacity: u64::MAX,
+                message_refill_per_sec: f64::MAX,
+                token_burst_capacity: u64::MAX,
+                token_refill_per_sec: f64::MAX,
+                max_messages_per_minute: u64::MAX,
+                max_tokens_per_minute: u64::MAX,
             },
         }
     }
@@ -97,10 +133,121 @@ pub struct TenantQuotas {
     pub max_endpoints: u32,
     pub max_concurrent_requests: u32,
     pub storage_bytes: u64,
+    /// Token-bucket burst capacity for messages: how many can be sent back
+    /// to back before throttling kicks in ahead of `max_messages_per_day`.
+    pub message_burst_capacity: u64,
+    /// Token-bucket refill rate for messages, in messages/sec
+    /// (`max_messages_per_minute / 60`).
+    pub message_refill_per_sec: f64,
+    /// Token-bucket burst capacity for tokens.
+    pub token_burst_capacity: u64,
+    /// Token-bucket refill rate for tokens, in tokens/sec
+    /// (`max_tokens_per_minute / 60`).
+    pub token_refill_per_sec: f64,
+    /// Sliding 60s-window cap on messages, enforced independently of the
+    /// burst bucket for smoother per-minute throttling.
+    pub max_messages_per_minute: u64,
+    /// Sliding 60s-window cap on tokens.
+    pub max_tokens_per_minute: u64,
+}
+
+/// Token bucket for one per-tenant quota resource (messages or tokens).
+/// Unlike `ratelimit::TokenBucket`, this one is only ever mutated while the
+/// caller holds the tenant's `DashMap` entry lock (via `usage.get_mut`), so
+/// plain fields suffice without atomics.
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, refill_per_sec: f64) -> Self {
+        Self { capacity, tokens: capacity, refill_per_sec, last_refill: Instant::now() }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Current level, after refilling.
+    fn level(&mut self) -> f64 {
+        self.refill();
+        self.tokens
+    }
+
+    /// Debit `amount`. Callers must have already checked `level() >=
+    /// amount`; this never goes negative but doesn't itself report failure.
+    fn consume(&mut self, amount: f64) {
+        self.tokens = (self.tokens - amount).max(0.0);
+    }
+
+    /// Live-retune capacity/rate (e.g. on `TenantManager::update_tier`),
+    /// clamping the current level to the new capacity rather than
+    /// resetting it.
+    fn retune(&mut self, capacity: f64, refill_per_sec: f64) {
+        self.refill();
+        self.capacity = capacity;
+        self.refill_per_sec = refill_per_sec;
+        self.tokens = self.tokens.min(capacity);
+    }
+}
+
+/// Seconds of history kept by each `SlidingWindowCounter`.
+const WINDOW_SECS: usize = 60;
+
+/// Ring of per-second counters summed over the trailing `WINDOW_SECS`
+/// seconds, for smoother messages/tokens-per-minute enforcement than a
+/// token bucket alone (a bucket allows a full burst all at once; this caps
+/// the total over the trailing minute regardless of how it's spread out).
+#[derive(Debug, Clone)]
+struct SlidingWindowCounter {
+    buckets: [u64; WINDOW_SECS],
+    /// Ring slot last written to.
+    current_slot: usize,
+    /// When `current_slot` was entered.
+    slot_started_at: Instant,
+}
+
+impl SlidingWindowCounter {
+    fn new() -> Self {
+        Self { buckets: [0; WINDOW_SECS], current_slot: 0, slot_started_at: Instant::now() }
+    }
+
+    /// Advance the ring to the current second, zeroing any seconds skipped
+    /// over (e.g. after an idle gap longer than the window).
+    fn advance(&mut self) {
+        let elapsed = self.slot_started_at.elapsed().as_secs() as usize;
+        if elapsed == 0 {
+            return;
+        }
+        let steps = elapsed.min(WINDOW_SECS);
+        for i in 1..=steps {
+            self.buckets[(self.current_slot + i) % WINDOW_SECS] = 0;
+        }
+        self.current_slot = (self.current_slot + elapsed) % WINDOW_SECS;
+        self.slot_started_at = Instant::now();
+    }
+
+    /// Sum across the trailing window, after advancing.
+    fn sum(&mut self) -> u64 {
+        self.advance();
+        self.buckets.iter().sum()
+    }
+
+    fn record(&mut self, amount: u64) {
+        self.advance();
+        self.buckets[self.current_slot] += amount;
+    }
 }
 
 /// Tenant usage tracking
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct TenantUsage {
     pub agents_count: u64,
     pub messages_today: u64,
@@ -109,9 +256,68 @@ pub struct TenantUsage {
     pub concurrent_requests: u32,
     pub storage_used: u64,
     pub last_reset: i64,
+    /// Throttles bursty message submission ahead of `max_messages_per_day`.
+    message_bucket: TokenBucket,
+    /// Throttles bursty token consumption ahead of `max_tokens_per_day`.
+    token_bucket: TokenBucket,
+    /// Sliding-window messages/minute counter.
+    message_window: SlidingWindowCounter,
+    /// Sliding-window tokens/minute counter.
+    token_window: SlidingWindowCounter,
 }
 
 impl TenantUsage {
+    /// Build fresh usage tracking for a tenant on `quotas`'s tier, with
+    /// full token buckets and empty sliding windows.
+    fn new(quotas: &TenantQuotas) -> Self {
+        Self {
+            agents_count: 0,
+            messages_today: 0,
+            tokens_today: 0,
+            endpoints_count: 0,
+            concurrent_requests: 0,
+            storage_used: 0,
+            last_reset: 0,
+            message_bucket: TokenBucket::new(quotas.message_burst_capacity as f64, quotas.message_refill_per_sec),
+            token_bucket: TokenBucket::new(quotas.token_burst_capacity as f64, quotas.token_refill_per_sec),
+            message_window: SlidingWindowCounter::new(),
+            token_window: SlidingWindowCounter::new(),
+        }
+    }
+
+    /// Throttle one message costing `tokens`, against the per-tenant
+    /// burst buckets and sliding windows, ahead of (and independent from)
+    /// the hard daily caps in `check_quota`. Checks all four limits before
+    /// debiting any of them, so a rejected call has no side effects.
+    fn check_and_throttle(&mut self, quotas: &TenantQuotas, tokens: u64) -> Result<(), TenantError> {
+        if self.message_bucket.level() < 1.0 {
+            return Err(TenantError::RateLimited(format!(
+                "message burst capacity ({:.0}) exhausted", self.message_bucket.capacity
+            )));
+        }
+        if self.token_bucket.level() < tokens as f64 {
+            return Err(TenantError::RateLimited(format!(
+                "token burst capacity ({:.0}) exhausted", self.token_bucket.capacity
+            )));
+        }
+        if self.message_window.sum() + 1 > quotas.max_messages_per_minute {
+            return Err(TenantError::RateLimited(format!(
+                "messages-per-minute limit ({}) exceeded", quotas.max_messages_per_minute
+            )));
+        }
+        if self.token_window.sum() + tokens > quotas.max_tokens_per_minute {
+            return Err(TenantError::RateLimited(format!(
+                "tokens-per-minute limit ({}) exceeded", quotas.max_tokens_per_minute
+            )));
+        }
+
+        self.message_bucket.consume(1.0);
+        self.token_bucket.consume(tokens as f64);
+        self.message_window.record(1);
+        self.token_window.record(tokens);
+        Ok(())
+    }
+
     /// Check if quota exceeded
     pub fn check_quota(&self, quotas: &TenantQuotas) -> Result<(), TenantError> {
         if self.agents_count >= quotas.max_agents {
@@ -135,13 +341,19 @@ impl TenantUsage {
         Ok(())
     }
     
-    /// Get utilization percentages
-    pub fn utilization(&self, quotas: &TenantQuotas) -> HashMap<String, f64> {
+    /// Get utilization percentages, plus the current throttle state: token
+    /// bucket levels and sliding-window rates, for observability into the
+    /// limits `check_and_throttle` enforces.
+    pub fn utilization(&mut self, quotas: &TenantQuotas) -> HashMap<String, f64> {
         let mut util = HashMap::new();
         util.insert("agents".into(), self.agents_count as f64 / quotas.max_agents as f64 * 100.0);
         util.insert("messages".into(), self.messages_today as f64 / quotas.max_messages_per_day as f64 * 100.0);
         util.insert("tokens".into(), self.tokens_today as f64 / quotas.max_tokens_per_day as f64 * 100.0);
         util.insert("storage".into(), self.storage_used as f64 / quotas.storage_bytes as f64 * 100.0);
+        util.insert("message_bucket_level".into(), self.message_bucket.level());
+        util.insert("token_bucket_level".into(), self.token_bucket.level());
+        util.insert("messages_per_minute_rate".into(), self.message_window.sum() as f64);
+        util.insert("tokens_per_minute_rate".into(), self.token_window.sum() as f64);
         util
     }
 }
@@ -181,12 +393,46 @@ impl Tenant {
     }
 }
 
+/// The status a tenant held before a lifecycle transition reserved it,
+/// recorded by `TenantManager::begin_transition` so `TenantTransitionGuard`
+/// can restore it on drop.
+struct InProgressSlot {
+    prior_status: TenantStatus,
+}
+
+/// RAII reservation held by a lifecycle operation (`update_tier`,
+/// `suspend_tenant`, `delete_tenant`) for the duration of its mutation.
+/// Dropping the guard restores the tenant's pre-transition status (a no-op
+/// if the tenant was removed, e.g. by `delete_tenant`) and releases the
+/// slot, so a concurrent `begin_transition` on the same id can proceed.
+struct TenantTransitionGuard<'a> {
+    manager: &'a TenantManager,
+    tenant_id: String,
+}
+
+impl Drop for TenantTransitionGuard<'_> {
+    fn drop(&mut self) {
+        if let Some((_, slot)) = self.manager.in_progress.remove(&self.tenant_id) {
+            if let Some(mut tenant) = self.manager.tenants.get_mut(&self.tenant_id) {
+                tenant.status = slot.prior_status;
+            }
+        }
+    }
+}
+
 /// Multi-tenant manager
 pub struct TenantManager {
     tenants: DashMap<String, Tenant>,
     usage: DashMap<String, TenantUsage>,
     /// Agent to tenant mapping
     agent_tenants: DashMap<String, String>,
+    /// Agent to bound peer-certificate identity (e.g. the SAN/CN returned by
+    /// `TransportLayer::peer_identity`), so a connection's TLS identity can
+    /// be checked against what the agent previously authenticated as.
+    agent_identities: DashMap<String, String>,
+    /// Tenant ids currently reserved by an in-flight lifecycle transition;
+    /// see `begin_transition`.
+    in_progress: DashMap<String, InProgressSlot>,
 }
 
 impl TenantManager {
@@ -195,14 +441,36 @@ impl TenantManager {
             tenants: DashMap::new(),
             usage: DashMap::new(),
             agent_tenants: DashMap::new(),
+            agent_identities: DashMap::new(),
+            in_progress: DashMap::new(),
         }
     }
+
+    /// Reserve `tenant_id` for an exclusive lifecycle transition, recording
+    /// its current status so the returned guard can restore it on drop.
+    /// The tenant's per-entry lock (held across this check-and-set) makes
+    /// two concurrent calls for the same id race-free: the loser observes
+    /// `TenantStatus::InProgress` and gets `TenantError::InProgress` instead
+    /// of stomping the winner's in-flight mutation.
+    fn begin_transition(&self, tenant_id: &str) -> Result<TenantTransitionGuard<'_>, TenantError> {
+        let mut tenant = self.tenants.get_mut(tenant_id)
+            .ok_or_else(|| TenantError::NotFound(tenant_id.to_string()))?;
+        if tenant.status == TenantStatus::InProgress {
+            return Err(TenantError::InProgress(tenant_id.to_string()));
+        }
+        let prior_status = tenant.status;
+        tenant.status = TenantStatus::InProgress;
+        drop(tenant);
+
+        self.in_progress.insert(tenant_id.to_string(), InProgressSlot { prior_status });
+        Ok(TenantTransitionGuard { manager: self, tenant_id: tenant_id.to_string() })
+    }
     
     /// Create a new tenant
     pub fn create_tenant(&self, id: String, name: String, tier: TenantTier) -> Result<Tenant, TenantError> {
         let tenant = Tenant::new(id.clone(), name, tier);
+        self.usage.insert(id.clone(), TenantUsage::new(&tenant.quotas));
         self.tenants.insert(id.clone(), tenant.clone());
-        self.usage.insert(id, TenantUsage::default());
         info!(tenant_id = %tenant.id, tier = ?tier, "Created tenant");
         Ok(tenant)
     }
@@ -211,43 +479,76 @@ impl TenantManager {
     pub fn get_tenant(&self, tenant_id: &str) -> Option<Tenant> {
         self.tenants.get(tenant_id).map(|t| t.clone())
     }
+
+    /// Like `get_tenant`, but waits (up to `timeout`) for any in-flight
+    /// lifecycle transition on `tenant_id` to release its slot first,
+    /// instead of reading a tenant mid-transition. Returns whatever's
+    /// there once the slot clears or `timeout` elapses, whichever is
+    /// first.
+    pub fn get_tenant_with_timeout(&self, tenant_id: &str, timeout: Duration) -> Option<Tenant> {
+        let deadline = Instant::now() + timeout;
+        while self.in_progress.contains_key(tenant_id) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        self.get_tenant(tenant_id)
+    }
     
-    /// Update tenant tier
+    /// Update tenant tier. Reserves the tenant via `begin_transition` for
+    /// the duration of the tier/quota swap so a concurrent `record_message`
+    /// or `register_agent` never observes a half-migrated tenant.
     pub fn update_tier(&self, tenant_id: &str, tier: TenantTier) -> Result<(), TenantError> {
+        let guard = self.begin_transition(tenant_id)?;
+        let quotas = tier.default_quotas();
         if let Some(mut tenant) = self.tenants.get_mut(tenant_id) {
             tenant.tier = tier;
-            tenant.quotas = tier.default_quotas();
-            info!(tenant_id = %tenant_id, tier = ?tier, "Updated tenant tier");
-            Ok(())
-        } else {
-            Err(TenantError::NotFound(tenant_id.to_string()))
+            tenant.quotas = quotas.clone();
         }
+        // Retune the burst buckets in place rather than resetting usage,
+        // so a tier change doesn't hand out a fresh full burst for free.
+        if let Some(mut usage) = self.usage.get_mut(tenant_id) {
+            usage.message_bucket.retune(quotas.message_burst_capacity as f64, quotas.message_refill_per_sec);
+            usage.token_bucket.retune(quotas.token_burst_capacity as f64, quotas.token_refill_per_sec);
+        }
+        drop(guard);
+        info!(tenant_id = %tenant_id, tier = ?tier, "Updated tenant tier");
+        Ok(())
     }
-    
-    /// Suspend a tenant
+
+    /// Suspend a tenant. Reserves it via `begin_transition` like
+    /// `update_tier`, then overrides the status the guard will restore on
+    /// drop to `Suspended` (rather than whatever status preceded the
+    /// transition), since suspension is meant to stick.
     pub fn suspend_tenant(&self, tenant_id: &str) -> Result<(), TenantError> {
-        if let Some(mut tenant) = self.tenants.get_mut(tenant_id) {
-            tenant.status = TenantStatus::Suspended;
-            info!(tenant_id = %tenant_id, "Suspended tenant");
-            Ok(())
-        } else {
-            Err(TenantError::NotFound(tenant_id.to_string()))
+        let guard = self.begin_transition(tenant_id)?;
+        if let Some(mut slot) = self.in_progress.get_mut(tenant_id) {
+            slot.prior_status = TenantStatus::Suspended;
         }
+        drop(guard);
+        info!(tenant_id = %tenant_id, "Suspended tenant");
+        Ok(())
     }
     
-    /// Activate a tenant
+    /// Activate a tenant. Reserves it via `begin_transition` like
+    /// `suspend_tenant`, then overrides the status the guard will restore on
+    /// drop to `Active` (rather than whatever status preceded the
+    /// transition), so a concurrent transition can't clobber the activation
+    /// or slip past the guard this call holds.
     pub fn activate_tenant(&self, tenant_id: &str) -> Result<(), TenantError> {
-        if let Some(mut tenant) = self.tenants.get_mut(tenant_id) {
-            tenant.status = TenantStatus::Active;
-            info!(tenant_id = %tenant_id, "Activated tenant");
-            Ok(())
-        } else {
-            Err(TenantError::NotFound(tenant_id.to_string()))
+        let guard = self.begin_transition(tenant_id)?;
+        if let Some(mut slot) = self.in_progress.get_mut(tenant_id) {
+            slot.prior_status = TenantStatus::Active;
         }
+        drop(guard);
+        info!(tenant_id = %tenant_id, "Activated tenant");
+        Ok(())
     }
     
     /// Register an agent to a tenant
     pub fn register_agent(&self, agent_id: &str, tenant_id: &str) -> Result<(), TenantError> {
+        if self.in_progress.contains_key(tenant_id) {
+            return Err(TenantError::InProgress(tenant_id.to_string()));
+        }
+
         // Verify tenant exists and is active
         let tenant = self.tenants.get(tenant_id)
             .ok_or_else(|| TenantError::NotFound(tenant_id.to_string()))?;
@@ -271,9 +572,36 @@ impl TenantManager {
     pub fn get_agent_tenant(&self, agent_id: &str) -> Option<String> {
         self.agent_tenants.get(agent_id).map(|t| t.clone())
     }
-    
+
+    /// Bind `agent_id` to the peer identity it authenticated with (e.g. a
+    /// TLS client-certificate SAN), so future connections claiming that
+    /// agent can be checked with `verify_identity`. Re-binding overwrites
+    /// the previous identity, e.g. after a certificate rotation.
+    pub fn bind_identity(&self, agent_id: &str, identity: &str) {
+        self.agent_identities.insert(agent_id.to_string(), identity.to_string());
+        debug!(agent_id = %agent_id, identity = %identity, "Bound agent identity");
+    }
+
+    /// Check that `identity` matches what `agent_id` is bound to. An agent
+    /// with no binding yet passes, so identity enforcement is opt-in per
+    /// agent until `bind_identity` has been called for it.
+    pub fn verify_identity(&self, agent_id: &str, identity: &str) -> Result<(), TenantError> {
+        match self.agent_identities.get(agent_id) {
+            Some(expected) if expected.as_str() != identity => Err(TenantError::IdentityMismatch {
+                agent_id: agent_id.to_string(),
+                expected: expected.clone(),
+                got: identity.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
     /// Record message for tenant
     pub fn record_message(&self, tenant_id: &str, tokens: u64) -> Result<(), TenantError> {
+        if self.in_progress.contains_key(tenant_id) {
+            return Err(TenantError::InProgress(tenant_id.to_string()));
+        }
+
         let tenant = self.tenants.get(tenant_id)
             .ok_or_else(|| TenantError::NotFound(tenant_id.to_string()))?;
         
@@ -283,13 +611,14 @@ impl TenantManager {
         
         if let Some(mut usage) = self.usage.get_mut(tenant_id) {
             usage.check_quota(&tenant.quotas)?;
+            usage.check_and_throttle(&tenant.quotas, tokens)?;
             usage.messages_today += 1;
             usage.tokens_today += tokens;
         }
-        
+
         Ok(())
     }
-    
+
     /// Get tenant usage
     pub fn get_usage(&self, tenant_id: &str) -> Option<TenantUsage> {
         self.usage.get(tenant_id).map(|u| u.clone())
@@ -315,13 +644,31 @@ impl TenantManager {
         self.tenants.iter().map(|t| t.clone()).collect()
     }
     
-    /// Delete a tenant
-    pub fn delete_tenant(&self, tenant_id: &str) -> bool {
+    /// Delete a tenant. Reserves it via `begin_transition` before removing
+    /// it so a concurrent `update_tier`/`suspend_tenant`/`record_message`
+    /// can't race the removal; idempotent like before for an id that's
+    /// already gone (nothing to reserve, so no `InProgress` slot is taken).
+    pub fn delete_tenant(&self, tenant_id: &str) -> Result<bool, TenantError> {
+        if !self.tenants.contains_key(tenant_id) {
+            return Ok(true);
+        }
+
+        let guard = self.begin_transition(tenant_id)?;
         self.tenants.remove(tenant_id);
         self.usage.remove(tenant_id);
         // Remove agent mappings
+        let removed_agents: Vec<String> = self
+            .agent_tenants
+            .iter()
+            .filter(|e| e.value() == tenant_id)
+            .map(|e| e.key().clone())
+            .collect();
         self.agent_tenants.retain(|_, v| v != tenant_id);
-        true
+        for agent_id in removed_agents {
+            self.agent_identities.remove(&agent_id);
+        }
+        drop(guard);
+        Ok(true)
     }
 }
 
@@ -363,26 +710,174 @@ mod tests {
     fn test_quota_check() {
         let manager = TenantManager::new();
         manager.create_tenant("t1".into(), "Test".into(), TenantTier::Free).unwrap();
-        
-        // Free tier has 1000 messages/day limit
-        for _ in 0..1000 {
+
+        // Free tier's daily cap is 1000 messages, but its burst bucket
+        // (capacity 20, refill 1/sec) throttles a tight loop well before
+        // that, so this now fails on `RateLimited` rather than running
+        // all the way to the daily `QuotaExceeded`.
+        for _ in 0..20 {
             manager.record_message("t1", 10).unwrap();
         }
-        
-        // Should fail now
+
         let result = manager.record_message("t1", 10);
-        assert!(result.is_err());
+        assert!(matches!(result, Err(TenantError::RateLimited(_))));
     }
-    
+
+    #[test]
+    fn test_token_bucket_rejects_once_burst_capacity_drains() {
+        let mut bucket = TokenBucket::new(5.0, 1.0);
+        for _ in 0..5 {
+            assert!(bucket.level() >= 1.0);
+            bucket.consume(1.0);
+        }
+        assert!(bucket.level() < 1.0);
+    }
+
+    #[test]
+    fn test_sliding_window_counter_sums_recorded_amounts() {
+        let mut window = SlidingWindowCounter::new();
+        window.record(10);
+        window.record(5);
+        assert_eq!(window.sum(), 15);
+    }
+
+    #[test]
+    fn test_tokens_per_minute_window_rejects_oversized_single_message() {
+        let manager = TenantManager::new();
+        manager.create_tenant("t1".into(), "Test".into(), TenantTier::Free).unwrap();
+
+        // Free tier caps tokens/minute at 2,000; a single message costing
+        // more than that is rejected even though the burst bucket alone
+        // (capacity 4,000) would have allowed it.
+        let result = manager.record_message("t1", 2_001);
+        assert!(matches!(result, Err(TenantError::RateLimited(_))));
+    }
+
+    #[test]
+    fn test_update_tier_retunes_burst_bucket_without_resetting_daily_usage() {
+        let manager = TenantManager::new();
+        manager.create_tenant("t1".into(), "Test".into(), TenantTier::Free).unwrap();
+        manager.record_message("t1", 10).unwrap();
+
+        manager.update_tier("t1", TenantTier::Starter).unwrap();
+
+        // Daily counters survive the tier change...
+        assert_eq!(manager.get_usage("t1").unwrap().messages_today, 1);
+
+        // ...and the burst bucket is retuned in place: its *level* carries
+        // over (no free top-up), but once drained, the ceiling it reports
+        // is Starter's new capacity (200), not Free's (20).
+        for _ in 0..19 {
+            manager.record_message("t1", 1).unwrap();
+        }
+        let result = manager.record_message("t1", 1);
+        assert!(matches!(&result, Err(TenantError::RateLimited(msg)) if msg.contains("200")));
+    }
+
     #[test]
     fn test_suspend_tenant() {
         let manager = TenantManager::new();
         manager.create_tenant("t1".into(), "Test".into(), TenantTier::Free).unwrap();
         
         manager.suspend_tenant("t1").unwrap();
-        
+
         // Should fail to register agent
         let result = manager.register_agent("agent-1", "t1");
         assert!(matches!(result, Err(TenantError::Suspended(_))));
     }
+
+    #[test]
+    fn test_concurrent_transition_on_same_tenant_is_rejected() {
+        let manager = TenantManager::new();
+        manager.create_tenant("t1".into(), "Test".into(), TenantTier::Free).unwrap();
+
+        let guard = manager.begin_transition("t1").unwrap();
+        let result = manager.update_tier("t1", TenantTier::Starter);
+        assert!(matches!(result, Err(TenantError::InProgress(_))));
+
+        drop(guard);
+        // The slot is released and the tenant's prior status restored, so
+        // the transition can now proceed.
+        assert!(manager.update_tier("t1", TenantTier::Starter).is_ok());
+        assert_eq!(manager.get_tenant("t1").unwrap().tier, TenantTier::Starter);
+    }
+
+    #[test]
+    fn test_usage_recording_is_rejected_while_a_transition_is_in_progress() {
+        let manager = TenantManager::new();
+        manager.create_tenant("t1".into(), "Test".into(), TenantTier::Free).unwrap();
+
+        let guard = manager.begin_transition("t1").unwrap();
+        assert!(matches!(manager.record_message("t1", 10), Err(TenantError::InProgress(_))));
+        assert!(matches!(manager.register_agent("agent-1", "t1"), Err(TenantError::InProgress(_))));
+
+        drop(guard);
+        assert!(manager.record_message("t1", 10).is_ok());
+    }
+
+    #[test]
+    fn test_get_tenant_with_timeout_waits_for_slot_to_clear() {
+        let manager = std::sync::Arc::new(TenantManager::new());
+        manager.create_tenant("t1".into(), "Test".into(), TenantTier::Free).unwrap();
+
+        let guard = manager.begin_transition("t1").unwrap();
+        let waiter = {
+            let manager = manager.clone();
+            std::thread::spawn(move || manager.get_tenant_with_timeout("t1", Duration::from_secs(5)))
+        };
+
+        std::thread::sleep(Duration::from_millis(20));
+        drop(guard);
+
+        let tenant = waiter.join().unwrap().unwrap();
+        assert_eq!(tenant.status, TenantStatus::Active);
+    }
+
+    #[test]
+    fn test_suspend_tenant_sticks_across_the_transition() {
+        let manager = TenantManager::new();
+        manager.create_tenant("t1".into(), "Test".into(), TenantTier::Free).unwrap();
+
+        manager.suspend_tenant("t1").unwrap();
+        assert_eq!(manager.get_tenant("t1").unwrap().status, TenantStatus::Suspended);
+    }
+
+    #[test]
+    fn test_activate_tenant_goes_through_begin_transition() {
+        let manager = TenantManager::new();
+        manager.create_tenant("t1".into(), "Test".into(), TenantTier::Free).unwrap();
+        manager.suspend_tenant("t1").unwrap();
+
+        manager.activate_tenant("t1").unwrap();
+        assert_eq!(manager.get_tenant("t1").unwrap().status, TenantStatus::Active);
+
+        // A transition already in flight rejects a concurrent activate,
+        // same as it would reject any other lifecycle mutator.
+        let guard = manager.begin_transition("t1").unwrap();
+        assert!(matches!(manager.activate_tenant("t1"), Err(TenantError::InProgress(_))));
+        drop(guard);
+    }
+
+    #[test]
+    fn test_delete_tenant_is_idempotent_for_an_unknown_id() {
+        let manager = TenantManager::new();
+        assert!(manager.delete_tenant("never-existed").unwrap());
+    }
+
+    #[test]
+    fn test_verify_identity_passes_without_a_binding() {
+        let manager = TenantManager::new();
+        assert!(manager.verify_identity("agent-1", "agent-1.aimesh").is_ok());
+    }
+
+    #[test]
+    fn test_verify_identity_rejects_mismatch_after_binding() {
+        let manager = TenantManager::new();
+        manager.bind_identity("agent-1", "agent-1.aimesh");
+
+        assert!(manager.verify_identity("agent-1", "agent-1.aimesh").is_ok());
+
+        let result = manager.verify_identity("agent-1", "someone-else.aimesh");
+        assert!(matches!(result, Err(TenantError::IdentityMismatch { .. })));
+    }
 }
@@ -3,12 +3,17 @@
 //! QUIC-based transport layer for low-latency AI message delivery.
 //! Targets 5M+ msgs/sec with <1ms P99 latency using quinn.
 
+use std::fs::File;
+use std::io::BufReader;
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
-use quinn::{ClientConfig, Endpoint, ServerConfig, Connection, RecvStream, SendStream};
+use quinn::{ClientConfig, Endpoint, ServerConfig, Connection, RecvStream, SendStream, VarInt};
 use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::RootCertStore;
 use tokio::sync::RwLock;
 use thiserror::Error;
 use tracing::{debug, info, warn, error};
@@ -30,8 +35,40 @@ pub enum TransportError {
     Timeout,
     #[error("Connection closed")]
     ConnectionClosed,
+    #[error("datagram payload of {size} bytes exceeds the peer's max datagram size of {max} bytes")]
+    PayloadTooLarge { size: usize, max: usize },
+    #[error("wire protocol mismatch: expected version {expected}, peer framed {got}")]
+    ProtocolMismatch { expected: u8, got: u8 },
+    #[error("no dialable address registered for endpoint {0}")]
+    EndpointNotFound(String),
 }
 
+/// ALPN identifier negotiated during the QUIC/TLS handshake so an AiMesh
+/// client can't silently complete a handshake with an unrelated (or
+/// future-incompatible) QUIC server.
+const ALPN_PROTOCOL: &[u8] = b"aimesh/1";
+
+/// Magic byte leading every stream frame, so a peer speaking a different
+/// wire format is rejected immediately instead of producing a garbage
+/// length prefix.
+const FRAME_MAGIC: u8 = 0xA5;
+
+/// Wire framing version carried in every stream frame. Bump this whenever
+/// the frame layout changes incompatibly; `read_message` rejects anything
+/// else with `TransportError::ProtocolMismatch` so a rolling upgrade fails
+/// fast and diagnosably instead of misparsing.
+const PROTOCOL_VERSION: u8 = 1;
+
+/// Bytes in a stream frame header: magic (1) + version (1) + length (4).
+const FRAME_HEADER_LEN: usize = 6;
+
+/// Largest frame payload `read_message` will allocate a buffer for. Set
+/// above `MAX_PAYLOAD_SIZE` (the biggest valid `AiMessage`) to leave room for
+/// framing/encoding overhead, so a peer that completes the handshake but
+/// then claims a length near `u32::MAX` is rejected before the allocation,
+/// not after `AiMessage::deserialize` finally checks the payload size.
+const MAX_FRAME_PAYLOAD_SIZE: usize = crate::protocol::MAX_PAYLOAD_SIZE + 4096;
+
 /// Transport configuration
 #[derive(Debug, Clone)]
 pub struct TransportConfig {
@@ -41,10 +78,20 @@ pub struct TransportConfig {
     pub keep_alive_secs: u64,
     /// Connection idle timeout in seconds
     pub idle_timeout_secs: u64,
-    /// Max concurrent streams per connection
+    /// Max concurrent streams per connection, at full budget
     pub max_concurrent_streams: u32,
-    /// Flow control window size
+    /// Floor a connection's concurrent-stream cap is throttled down to as
+    /// its agent's remaining budget approaches zero; see
+    /// `TransportLayer::apply_budget_flow_control`. Never below this even
+    /// at zero remaining budget, so a depleted agent can still drain
+    /// in-flight work instead of being completely starved.
+    pub min_concurrent_streams: u32,
+    /// Per-stream flow control receive window, in bytes
     pub stream_window_size: u32,
+    /// Connection-level flow control receive window, in bytes
+    pub receive_window: u32,
+    /// TLS identity and trust configuration
+    pub tls: TlsConfig,
 }
 
 impl Default for TransportConfig {
@@ -54,11 +101,41 @@ impl Default for TransportConfig {
             keep_alive_secs: 30,
             idle_timeout_secs: 300,
             max_concurrent_streams: 1000,
+            min_concurrent_streams: 50,
             stream_window_size: 10 * 1024 * 1024, // 10MB
+            receive_window: 25 * 1024 * 1024, // 25MB
+            tls: TlsConfig::default(),
         }
     }
 }
 
+/// TLS identity and trust configuration for the QUIC transport.
+///
+/// With `cert_path`/`key_path` set, the server presents that PEM-loaded
+/// identity instead of a generated self-signed cert. With `ca_bundle_path`
+/// also set, the server additionally requires and validates client
+/// certificates against that bundle (mutual TLS), and the client validates
+/// the server's certificate against it instead of trusting anything. Only
+/// when none of this is configured does `allow_insecure` decide whether to
+/// fall back to the dev-only self-signed / skip-verification path;
+/// `TransportLayer` refuses to start rather than silently falling back.
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+    /// PEM file containing this node's certificate chain.
+    pub cert_path: Option<PathBuf>,
+    /// PEM file containing this node's PKCS#8 private key.
+    pub key_path: Option<PathBuf>,
+    /// PEM file containing trust-anchor CA certificates. When set, the
+    /// server enables mutual TLS and the client verifies the server's
+    /// certificate against these roots instead of skipping verification.
+    pub ca_bundle_path: Option<PathBuf>,
+    /// Explicit dev escape hatch: generate a self-signed cert (server) or
+    /// skip server certificate verification (client) when no real cert
+    /// material was configured. Must be opted into; never the silent
+    /// default.
+    pub allow_insecure: bool,
+}
+
 /// Connection pool for reusing connections
 pub struct ConnectionPool {
     connections: DashMap<String, Connection>,
@@ -94,8 +171,24 @@ impl Default for ConnectionPool {
 pub struct TransportLayer {
     config: TransportConfig,
     endpoint: Option<Endpoint>,
+    /// Client-role `Endpoint`, created lazily on the first `connect` and
+    /// reused for every subsequent one, so repeat connects share one socket
+    /// and the TLS session cache that makes 0-RTT resumption possible.
+    client_endpoint: tokio::sync::OnceCell<Endpoint>,
     connection_pool: Arc<ConnectionPool>,
     stats: Arc<RwLock<TransportStats>>,
+    /// Maps a routing-layer `endpoint_id` (e.g. `EndpointMetrics::endpoint_id`,
+    /// an opaque logical name) to the dialable address `connect` actually
+    /// parses as a `SocketAddr`. Populated via `register_endpoint_address`.
+    endpoint_addresses: DashMap<String, String>,
+    /// Maps a dialable address to the TLS server name (SNI) `connect` should
+    /// present for it, so hostname/SAN validation checks the peer's real
+    /// certificate instead of a placeholder. Populated via
+    /// `register_server_name`; addresses with no entry fall back to
+    /// `"localhost"`, which is what this module's test fixtures issue their
+    /// server certificates for, apart from the one that exercises
+    /// `register_server_name`.
+    endpoint_sni: DashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -115,10 +208,59 @@ impl TransportLayer {
         Ok(Self {
             config,
             endpoint: None,
+            client_endpoint: tokio::sync::OnceCell::new(),
             connection_pool: Arc::new(ConnectionPool::new()),
             stats: Arc::new(RwLock::new(TransportStats::default())),
+            endpoint_addresses: DashMap::new(),
+            endpoint_sni: DashMap::new(),
         })
     }
+
+    /// Register the dialable address for a routing-layer `endpoint_id`, so
+    /// `resolve_endpoint` can turn the opaque id callers route to back into
+    /// something `connect` can parse as a `SocketAddr`.
+    pub fn register_endpoint_address(&self, endpoint_id: &str, addr: &str) {
+        self.endpoint_addresses.insert(endpoint_id.to_string(), addr.to_string());
+    }
+
+    /// Register the TLS server name (SNI) `connect` should present when
+    /// dialing `addr`. Required for any peer whose certificate isn't issued
+    /// for `"localhost"` -- i.e. every real peer outside this module's test
+    /// fixtures -- since `connect` otherwise has no hostname to validate
+    /// against and cannot infer one from a bare `SocketAddr`.
+    pub fn register_server_name(&self, addr: &str, server_name: &str) {
+        self.endpoint_sni.insert(addr.to_string(), server_name.to_string());
+    }
+
+    /// Resolve a routing-layer `endpoint_id` to the address `connect`/`send`/
+    /// `send_datagram` should dial. Prefers an explicit
+    /// `register_endpoint_address` mapping; if none was registered, falls
+    /// back to treating `endpoint_id` itself as the address (so tests and
+    /// callers that already register endpoints under their literal dialable
+    /// address keep working unchanged). Errors if neither resolves to a
+    /// parseable `SocketAddr`.
+    pub fn resolve_endpoint(&self, endpoint_id: &str) -> Result<String, TransportError> {
+        let addr = self.endpoint_addresses
+            .get(endpoint_id)
+            .map(|a| a.clone())
+            .unwrap_or_else(|| endpoint_id.to_string());
+
+        addr.parse::<SocketAddr>()
+            .map(|_| addr)
+            .map_err(|_| TransportError::EndpointNotFound(endpoint_id.to_string()))
+    }
+
+    /// The shared client-role endpoint, created on first use.
+    async fn client_endpoint(&self) -> Result<&Endpoint, TransportError> {
+        self.client_endpoint
+            .get_or_try_init(|| async {
+                let mut endpoint = Endpoint::client("0.0.0.0:0".parse().unwrap())
+                    .map_err(|e| TransportError::BindError(e.to_string()))?;
+                endpoint.set_default_client_config(self.create_client_config()?);
+                Ok(endpoint)
+            })
+            .await
+    }
     
     /// Generate self-signed certificate for development
     fn generate_self_signed_cert() -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), TransportError> {
@@ -131,13 +273,86 @@ impl TransportLayer {
         Ok((vec![cert_der], key_der))
     }
     
-    /// Create server configuration
+    /// Load a PEM certificate chain from disk.
+    fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, TransportError> {
+        let file = File::open(path)
+            .map_err(|e| TransportError::TlsError(format!("opening cert {}: {}", path.display(), e)))?;
+        rustls_pemfile::certs(&mut BufReader::new(file))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| TransportError::TlsError(format!("parsing cert {}: {}", path.display(), e)))
+    }
+
+    /// Load a PEM PKCS#8 private key from disk.
+    fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, TransportError> {
+        let file = File::open(path)
+            .map_err(|e| TransportError::TlsError(format!("opening key {}: {}", path.display(), e)))?;
+        let mut keys = rustls_pemfile::pkcs8_private_keys(&mut BufReader::new(file))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| TransportError::TlsError(format!("parsing key {}: {}", path.display(), e)))?;
+        let key = keys.pop()
+            .ok_or_else(|| TransportError::TlsError(format!("no PKCS#8 private key found in {}", path.display())))?;
+        Ok(PrivateKeyDer::Pkcs8(key))
+    }
+
+    /// Load a PEM CA bundle into a `RootCertStore` of trust anchors.
+    fn load_root_store(path: &Path) -> Result<RootCertStore, TransportError> {
+        let mut store = RootCertStore::empty();
+        for cert in Self::load_certs(path)? {
+            store.add(cert)
+                .map_err(|e| TransportError::TlsError(format!("adding CA from {}: {}", path.display(), e)))?;
+        }
+        Ok(store)
+    }
+
+    /// Resolve this node's TLS identity: real PEM cert material if
+    /// configured, otherwise a generated self-signed cert if `allow_insecure`
+    /// opted into the dev path.
+    fn resolve_identity(&self) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), TransportError> {
+        match (&self.config.tls.cert_path, &self.config.tls.key_path) {
+            (Some(cert_path), Some(key_path)) => {
+                Ok((Self::load_certs(cert_path)?, Self::load_private_key(key_path)?))
+            }
+            _ if self.config.tls.allow_insecure => Self::generate_self_signed_cert(),
+            _ => Err(TransportError::TlsError(
+                "no certificate configured (tls.cert_path/key_path) and tls.allow_insecure is false".into(),
+            )),
+        }
+    }
+
+    /// Create server configuration: a real PEM identity (requiring and
+    /// validating client certs via `WebPkiClientVerifier` when a CA bundle
+    /// is configured, for mutual TLS) or, only with `allow_insecure`, the
+    /// dev self-signed/no-client-auth path.
     fn create_server_config(&self) -> Result<ServerConfig, TransportError> {
-        let (certs, key) = Self::generate_self_signed_cert()?;
-        
-        let mut server_config = ServerConfig::with_single_cert(certs, key)
-            .map_err(|e| TransportError::TlsError(e.to_string()))?;
-        
+        let (certs, key) = self.resolve_identity()?;
+
+        let mut crypto = match &self.config.tls.ca_bundle_path {
+            Some(ca_path) => {
+                let roots = Arc::new(Self::load_root_store(ca_path)?);
+                let client_verifier = WebPkiClientVerifier::builder(roots)
+                    .build()
+                    .map_err(|e| TransportError::TlsError(format!("building client verifier: {}", e)))?;
+                rustls::ServerConfig::builder()
+                    .with_client_cert_verifier(client_verifier)
+                    .with_single_cert(certs, key)
+                    .map_err(|e| TransportError::TlsError(e.to_string()))?
+            }
+            None => rustls::ServerConfig::builder()
+                .with_no_client_auth()
+                .with_single_cert(certs, key)
+                .map_err(|e| TransportError::TlsError(e.to_string()))?,
+        };
+        // Accept 0-RTT early data from clients resuming a prior session.
+        crypto.max_early_data_size = u32::MAX;
+        // Require the AiMesh ALPN so an unrelated (or incompatible) QUIC
+        // peer fails the handshake instead of connecting successfully.
+        crypto.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+
+        let mut server_config = ServerConfig::with_crypto(Arc::new(
+            quinn::crypto::rustls::QuicServerConfig::try_from(crypto)
+                .map_err(|e| TransportError::TlsError(e.to_string()))?,
+        ));
+
         let transport_config = Arc::get_mut(&mut server_config.transport)
             .expect("transport config");
         transport_config.max_idle_timeout(Some(
@@ -148,17 +363,51 @@ impl TransportLayer {
         ));
         transport_config.max_concurrent_uni_streams(self.config.max_concurrent_streams.into());
         transport_config.max_concurrent_bidi_streams(self.config.max_concurrent_streams.into());
-        
+        transport_config.receive_window(self.config.receive_window.into());
+        transport_config.stream_receive_window(self.config.stream_window_size.into());
+
         Ok(server_config)
     }
-    
-    /// Create client configuration (skip certificate verification for dev)
-    fn create_client_config() -> Result<ClientConfig, TransportError> {
-        let crypto = rustls::ClientConfig::builder()
-            .dangerous()
-            .with_custom_certificate_verifier(Arc::new(SkipServerVerification))
-            .with_no_client_auth();
-        
+
+    /// Create client configuration: verify the server's certificate against
+    /// the configured CA bundle via the standard `WebPkiServerVerifier`, or,
+    /// only with `allow_insecure`, skip verification entirely for dev. When
+    /// `tls.cert_path`/`key_path` are also set, present that identity as the
+    /// client certificate so a server requiring mutual TLS can authenticate
+    /// us (see `TransportLayer::peer_identity`).
+    fn create_client_config(&self) -> Result<ClientConfig, TransportError> {
+        let builder = match &self.config.tls.ca_bundle_path {
+            Some(ca_path) => {
+                let roots = Self::load_root_store(ca_path)?;
+                rustls::ClientConfig::builder().with_root_certificates(roots)
+            }
+            None if self.config.tls.allow_insecure => rustls::ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(SkipServerVerification)),
+            None => {
+                return Err(TransportError::TlsError(
+                    "no CA bundle configured (tls.ca_bundle_path) and tls.allow_insecure is false".into(),
+                ));
+            }
+        };
+
+        let mut crypto = match (&self.config.tls.cert_path, &self.config.tls.key_path) {
+            (Some(cert_path), Some(key_path)) => {
+                let certs = Self::load_certs(cert_path)?;
+                let key = Self::load_private_key(key_path)?;
+                builder.with_client_auth_cert(certs, key)
+                    .map_err(|e| TransportError::TlsError(format!("client auth cert: {}", e)))?
+            }
+            _ => builder.with_no_client_auth(),
+        };
+        // Resumption is on by default; opt into 0-RTT early data too so a
+        // reconnect with a valid session ticket can send its first stream
+        // in the initial flight instead of waiting a full round trip.
+        crypto.enable_early_data = true;
+        // Offer the AiMesh ALPN; the handshake fails if the server doesn't
+        // also negotiate it.
+        crypto.alpn_protocols = vec![ALPN_PROTOCOL.to_vec()];
+
         Ok(ClientConfig::new(Arc::new(
             quinn::crypto::rustls::QuicClientConfig::try_from(crypto)
                 .map_err(|e| TransportError::TlsError(e.to_string()))?
@@ -203,7 +452,79 @@ impl TransportLayer {
         
         Ok(connection)
     }
-    
+
+    /// Scale `max_concurrent_streams` down toward `min_concurrent_streams`
+    /// as `remaining_ratio` (an agent's remaining budget divided by its
+    /// initial allotment, see `CostAwareRouter::budget_ratio`) shrinks
+    /// toward zero. `remaining_ratio` is clamped to `[0.0, 1.0]` so a
+    /// caller doesn't need to pre-clamp a raw budget division.
+    pub fn scaled_concurrent_streams(&self, remaining_ratio: f64) -> u32 {
+        let ratio = remaining_ratio.clamp(0.0, 1.0);
+        let min = self.config.min_concurrent_streams;
+        let max = self.config.max_concurrent_streams;
+        min + ((max.saturating_sub(min)) as f64 * ratio) as u32
+    }
+
+    /// Narrow an already-accepted connection's concurrent-stream cap (both
+    /// directions) to `scaled_concurrent_streams(remaining_ratio)`, using
+    /// quinn's dynamic stream-limit update rather than tearing down and
+    /// recreating the connection.
+    pub fn apply_budget_flow_control(&self, connection: &Connection, remaining_ratio: f64) {
+        let cap = VarInt::from_u32(self.scaled_concurrent_streams(remaining_ratio));
+        connection.set_max_concurrent_uni_streams(cap);
+        connection.set_max_concurrent_bidi_streams(cap);
+    }
+
+    /// Accept a connection already known to belong to `agent_id`, and
+    /// immediately throttle its concurrent-stream cap from `router`'s
+    /// current budget state for that agent (see `apply_budget_flow_control`),
+    /// so a spender nearing its ceiling is narrowed at the transport layer
+    /// instead of only being rejected after a full routing pass.
+    pub async fn accept_for_agent(
+        &self,
+        agent_id: &str,
+        router: &crate::routing::CostAwareRouter,
+    ) -> Result<Connection, TransportError> {
+        let connection = self.accept().await?;
+        self.apply_budget_flow_control(&connection, router.budget_ratio(agent_id));
+        Ok(connection)
+    }
+
+    /// Extract a stable identity string from `connection`'s validated peer
+    /// certificate chain (populated only when the server required client
+    /// certificates, i.e. mutual TLS via `TlsConfig::ca_bundle_path`).
+    /// Prefers the leaf certificate's first DNS SAN, falling back to its
+    /// subject common name. Callers (e.g. the accept loop, or
+    /// `AiMesh::process_message`) use this to verify a message's claimed
+    /// `agent_id` actually belongs to the connection that sent it.
+    pub fn peer_identity(connection: &Connection) -> Result<String, TransportError> {
+        let identity = connection.peer_identity()
+            .ok_or_else(|| TransportError::TlsError("connection has no peer identity (is mTLS enabled?)".into()))?;
+        let certs = identity
+            .downcast::<Vec<CertificateDer<'static>>>()
+            .map_err(|_| TransportError::TlsError("unexpected peer identity type".into()))?;
+        let leaf = certs.first()
+            .ok_or_else(|| TransportError::TlsError("peer certificate chain is empty".into()))?;
+
+        let (_, cert) = x509_parser::parse_x509_certificate(leaf.as_ref())
+            .map_err(|e| TransportError::TlsError(format!("parsing peer certificate: {}", e)))?;
+
+        if let Ok(Some(san)) = cert.tbs_certificate.subject_alternative_name() {
+            for name in san.value.general_names.iter() {
+                if let x509_parser::extensions::GeneralName::DNSName(dns) = name {
+                    return Ok(dns.to_string());
+                }
+            }
+        }
+
+        cert.subject()
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .map(|s| s.to_string())
+            .ok_or_else(|| TransportError::TlsError("peer certificate has no SAN or common name".into()))
+    }
+
     /// Connect to a remote endpoint
     pub async fn connect(&self, addr: &str) -> Result<Connection, TransportError> {
         // Check connection pool first
@@ -216,19 +537,32 @@ impl TransportLayer {
         
         let socket_addr: SocketAddr = addr.parse()
             .map_err(|e| TransportError::ConnectionFailed(format!("Invalid address: {}", e)))?;
-        
-        // Create client endpoint
-        let mut endpoint = Endpoint::client("0.0.0.0:0".parse().unwrap())
-            .map_err(|e| TransportError::BindError(e.to_string()))?;
-        
-        endpoint.set_default_client_config(Self::create_client_config()?);
-        
-        // Connect
-        let connection = endpoint.connect(socket_addr, "localhost")
-            .map_err(|e| TransportError::ConnectionFailed(e.to_string()))?
-            .await
+
+        // Reuse the one shared client endpoint instead of binding a new
+        // socket per connect.
+        let endpoint = self.client_endpoint().await?;
+
+        let server_name = self.endpoint_sni
+            .get(addr)
+            .map(|s| s.clone())
+            .unwrap_or_else(|| "localhost".to_string());
+
+        let connecting = endpoint.connect(socket_addr, &server_name)
             .map_err(|e| TransportError::ConnectionFailed(e.to_string()))?;
-        
+
+        // If the endpoint still holds a valid session ticket for this
+        // server, this completes immediately with a 0-RTT connection whose
+        // first stream can be sent before the handshake finishes; otherwise
+        // it falls back to a normal 1-RTT handshake.
+        let connection = match connecting.into_0rtt() {
+            Ok((connection, _accepted)) => {
+                debug!(addr = %addr, "Sending 0-RTT early data");
+                connection
+            }
+            Err(connecting) => connecting.await
+                .map_err(|e| TransportError::ConnectionFailed(e.to_string()))?,
+        };
+
         info!(addr = %addr, "Connected to remote");
         
         // Store in pool
@@ -244,56 +578,106 @@ impl TransportLayer {
         // Open bidirectional stream
         let (mut send, mut recv) = connection.open_bi().await
             .map_err(|e| TransportError::SendFailed(e.to_string()))?;
-        
-        // Send data with length prefix
-        let len = (data.len() as u32).to_be_bytes();
-        send.write_all(&len).await
-            .map_err(|e| TransportError::SendFailed(e.to_string()))?;
-        send.write_all(&data).await
-            .map_err(|e| TransportError::SendFailed(e.to_string()))?;
+
+        // Send the framed message
+        let frame_len = data.len() as u64 + FRAME_HEADER_LEN as u64;
+        self.write_message(&mut send, &data).await?;
         send.finish()
             .map_err(|e| TransportError::SendFailed(e.to_string()))?;
-        
+
         // Update stats
         {
             let mut stats = self.stats.write().await;
-            stats.bytes_sent += data.len() as u64 + 4;
+            stats.bytes_sent += frame_len;
             stats.messages_sent += 1;
         }
-        
+
         // Read response
         let response = self.read_message(&mut recv).await?;
-        
+
         // Update stats
         {
             let mut stats = self.stats.write().await;
-            stats.bytes_received += response.len() as u64 + 4;
+            stats.bytes_received += response.len() as u64 + FRAME_HEADER_LEN as u64;
             stats.messages_received += 1;
         }
-        
+
         Ok(response)
     }
-    
-    /// Read a length-prefixed message from a stream
+
+    /// Send `data` as a single unreliable, unordered QUIC datagram -- no
+    /// stream, no acknowledgment, no retransmission. Meant for best-effort
+    /// low-priority traffic that isn't worth a full reliable round trip.
+    /// Errors eagerly if `data` exceeds the peer-advertised
+    /// `max_datagram_size` instead of silently dropping it on send.
+    pub async fn send_datagram(&self, addr: &str, data: Vec<u8>) -> Result<(), TransportError> {
+        let connection = self.connect(addr).await?;
+
+        let max_size = connection.max_datagram_size()
+            .ok_or_else(|| TransportError::SendFailed("peer does not support datagrams".into()))?;
+        if data.len() > max_size {
+            return Err(TransportError::PayloadTooLarge { size: data.len(), max: max_size });
+        }
+
+        let len = data.len() as u64;
+        connection.send_datagram(data.into())
+            .map_err(|e| TransportError::SendFailed(e.to_string()))?;
+
+        let mut stats = self.stats.write().await;
+        stats.bytes_sent += len;
+        stats.messages_sent += 1;
+
+        Ok(())
+    }
+
+    /// Receive one unreliable datagram from `connection`. Call in a loop to
+    /// drain all arriving datagrams, mirroring `accept`.
+    pub async fn read_datagram(&self, connection: &Connection) -> Result<Vec<u8>, TransportError> {
+        let data = connection.read_datagram().await
+            .map_err(|e| TransportError::ReceiveFailed(e.to_string()))?;
+
+        let mut stats = self.stats.write().await;
+        stats.bytes_received += data.len() as u64;
+        stats.messages_received += 1;
+
+        Ok(data.to_vec())
+    }
+
+    /// Read a framed message from a stream: a magic byte and protocol
+    /// version (rejected via `TransportError::ProtocolMismatch` if either
+    /// doesn't match what we write), then a length-prefixed payload.
     pub async fn read_message(&self, recv: &mut RecvStream) -> Result<Vec<u8>, TransportError> {
-        // Read length prefix
-        let mut len_buf = [0u8; 4];
-        recv.read_exact(&mut len_buf).await
+        // Read frame header: magic (1) + version (1) + length (4)
+        let mut header = [0u8; FRAME_HEADER_LEN];
+        recv.read_exact(&mut header).await
             .map_err(|e| TransportError::ReceiveFailed(e.to_string()))?;
-        let len = u32::from_be_bytes(len_buf) as usize;
-        
+
+        let (magic, version) = (header[0], header[1]);
+        if magic != FRAME_MAGIC || version != PROTOCOL_VERSION {
+            return Err(TransportError::ProtocolMismatch { expected: PROTOCOL_VERSION, got: version });
+        }
+        let len = u32::from_be_bytes(header[2..6].try_into().unwrap()) as usize;
+        if len > MAX_FRAME_PAYLOAD_SIZE {
+            return Err(TransportError::PayloadTooLarge { size: len, max: MAX_FRAME_PAYLOAD_SIZE });
+        }
+
         // Read data
         let mut data = vec![0u8; len];
         recv.read_exact(&mut data).await
             .map_err(|e| TransportError::ReceiveFailed(e.to_string()))?;
-        
+
         Ok(data)
     }
-    
-    /// Write a length-prefixed message to a stream
+
+    /// Write a framed message to a stream: magic byte, protocol version,
+    /// then a length-prefixed payload (see `read_message`).
     pub async fn write_message(&self, send: &mut SendStream, data: &[u8]) -> Result<(), TransportError> {
-        let len = (data.len() as u32).to_be_bytes();
-        send.write_all(&len).await
+        let mut header = Vec::with_capacity(FRAME_HEADER_LEN);
+        header.push(FRAME_MAGIC);
+        header.push(PROTOCOL_VERSION);
+        header.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+        send.write_all(&header).await
             .map_err(|e| TransportError::SendFailed(e.to_string()))?;
         send.write_all(data).await
             .map_err(|e| TransportError::SendFailed(e.to_string()))?;
@@ -304,6 +688,16 @@ impl TransportLayer {
     pub async fn stats(&self) -> TransportStats {
         self.stats.read().await.clone()
     }
+
+    /// The address this transport is actually bound to, e.g. to discover
+    /// the real port after binding to `:0`. Only set once `listen` succeeds.
+    pub fn local_addr(&self) -> Result<SocketAddr, TransportError> {
+        self.endpoint
+            .as_ref()
+            .ok_or_else(|| TransportError::ConnectionFailed("Server not started".into()))?
+            .local_addr()
+            .map_err(|e| TransportError::BindError(e.to_string()))
+    }
     
     /// Close all connections
     pub fn close(&self) {
@@ -379,4 +773,478 @@ mod tests {
         let result = TransportLayer::generate_self_signed_cert();
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_create_server_config_refuses_without_cert_or_allow_insecure() {
+        let transport = TransportLayer::new(TransportConfig::default()).unwrap();
+        let result = transport.create_server_config();
+        assert!(matches!(result, Err(TransportError::TlsError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_create_client_config_refuses_without_ca_bundle_or_allow_insecure() {
+        let transport = TransportLayer::new(TransportConfig::default()).unwrap();
+        let result = transport.create_client_config();
+        assert!(matches!(result, Err(TransportError::TlsError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_create_server_and_client_config_succeed_with_allow_insecure() {
+        let mut config = TransportConfig::default();
+        config.tls.allow_insecure = true;
+        let transport = TransportLayer::new(config).unwrap();
+
+        assert!(transport.create_server_config().is_ok());
+        assert!(transport.create_client_config().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_server_config_loads_pem_cert_and_key_from_disk() {
+        let dir = std::env::temp_dir().join(format!("aimesh-tls-test-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+
+        let mut config = TransportConfig::default();
+        config.tls.cert_path = Some(cert_path);
+        config.tls.key_path = Some(key_path);
+        let transport = TransportLayer::new(config).unwrap();
+
+        assert!(transport.create_server_config().is_ok());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_server_config_enables_mutual_tls_with_ca_bundle() {
+        let dir = std::env::temp_dir().join(format!("aimesh-tls-test-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        let ca_path = dir.join("ca.pem");
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+        // Self-signed cert doubles as its own trust anchor for this test.
+        std::fs::write(&ca_path, cert.cert.pem()).unwrap();
+
+        let mut config = TransportConfig::default();
+        config.tls.cert_path = Some(cert_path);
+        config.tls.key_path = Some(key_path);
+        config.tls.ca_bundle_path = Some(ca_path.clone());
+        let transport = TransportLayer::new(config).unwrap();
+
+        assert!(transport.create_server_config().is_ok());
+
+        let mut client_config = TransportConfig::default();
+        client_config.tls.ca_bundle_path = Some(ca_path);
+        let client_transport = TransportLayer::new(client_config).unwrap();
+        assert!(client_transport.create_client_config().is_ok());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_peer_identity_extracts_san_from_client_cert() {
+        let dir = std::env::temp_dir().join(format!("aimesh-mtls-e2e-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let server_cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let server_cert_path = dir.join("server-cert.pem");
+        let server_key_path = dir.join("server-key.pem");
+        std::fs::write(&server_cert_path, server_cert.cert.pem()).unwrap();
+        std::fs::write(&server_key_path, server_cert.key_pair.serialize_pem()).unwrap();
+        // Self-signed, so it's also its own trust anchor for the client.
+        let server_ca_path = dir.join("server-ca.pem");
+        std::fs::write(&server_ca_path, server_cert.cert.pem()).unwrap();
+
+        let client_cert = rcgen::generate_simple_self_signed(vec!["agent-1.aimesh".into()]).unwrap();
+        let client_cert_path = dir.join("client-cert.pem");
+        let client_key_path = dir.join("client-key.pem");
+        std::fs::write(&client_cert_path, client_cert.cert.pem()).unwrap();
+        std::fs::write(&client_key_path, client_cert.key_pair.serialize_pem()).unwrap();
+        // Self-signed, so it's also its own trust anchor for the server's
+        // client-auth verifier.
+        let client_ca_path = dir.join("client-ca.pem");
+        std::fs::write(&client_ca_path, client_cert.cert.pem()).unwrap();
+
+        let mut server_config = TransportConfig::default();
+        server_config.bind_addr = "127.0.0.1:0".into();
+        server_config.tls.cert_path = Some(server_cert_path);
+        server_config.tls.key_path = Some(server_key_path);
+        server_config.tls.ca_bundle_path = Some(client_ca_path);
+
+        let mut server = TransportLayer::new(server_config).unwrap();
+        server.listen().await.unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let server_task = tokio::spawn(async move {
+            let conn = server.accept().await.unwrap();
+            TransportLayer::peer_identity(&conn).unwrap()
+        });
+
+        let mut client_config = TransportConfig::default();
+        client_config.tls.ca_bundle_path = Some(server_ca_path);
+        client_config.tls.cert_path = Some(client_cert_path);
+        client_config.tls.key_path = Some(client_key_path);
+        let client = TransportLayer::new(client_config).unwrap();
+        client.connect(&addr.to_string()).await.unwrap();
+
+        let identity = tokio::time::timeout(std::time::Duration::from_secs(5), server_task)
+            .await
+            .expect("server never accepted the mTLS connection")
+            .unwrap();
+
+        assert_eq!(identity, "agent-1.aimesh");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_connect_uses_registered_server_name_for_hostname_verification() {
+        let dir = std::env::temp_dir().join(format!("aimesh-sni-test-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Issued for a real hostname, not "localhost" -- the case the
+        // hardcoded SNI literal could never actually validate.
+        let server_cert = rcgen::generate_simple_self_signed(vec!["peer-a.aimesh".into()]).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        let ca_path = dir.join("ca.pem");
+        std::fs::write(&cert_path, server_cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, server_cert.key_pair.serialize_pem()).unwrap();
+        std::fs::write(&ca_path, server_cert.cert.pem()).unwrap();
+
+        let mut server_config = TransportConfig::default();
+        server_config.bind_addr = "127.0.0.1:0".into();
+        server_config.tls.cert_path = Some(cert_path);
+        server_config.tls.key_path = Some(key_path);
+        server_config.tls.allow_insecure = true;
+
+        let mut server = TransportLayer::new(server_config).unwrap();
+        server.listen().await.unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let server_task = tokio::spawn(async move {
+            server.accept().await.ok();
+        });
+
+        let mut client_config = TransportConfig::default();
+        client_config.tls.ca_bundle_path = Some(ca_path);
+        let client = TransportLayer::new(client_config).unwrap();
+
+        // Without a registered server name, `connect` falls back to
+        // "localhost", which doesn't match the cert's "peer-a.aimesh" SAN.
+        assert!(client.connect(&addr.to_string()).await.is_err());
+
+        // Registering the peer's real server name lets the handshake
+        // validate against its actual certificate.
+        client.register_server_name(&addr.to_string(), "peer-a.aimesh");
+        client.connect(&addr.to_string()).await.unwrap();
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), server_task)
+            .await
+            .expect("server never accepted the connection")
+            .ok();
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_repeat_connects_reuse_the_same_client_endpoint() {
+        let dir = std::env::temp_dir().join(format!("aimesh-0rtt-test-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+
+        let mut server_config = TransportConfig::default();
+        server_config.bind_addr = "127.0.0.1:0".into();
+        server_config.tls.cert_path = Some(cert_path);
+        server_config.tls.key_path = Some(key_path);
+        server_config.tls.allow_insecure = true;
+
+        let mut server = TransportLayer::new(server_config).unwrap();
+        server.listen().await.unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let server_task = tokio::spawn(async move {
+            for _ in 0..2 {
+                server.accept().await.unwrap();
+            }
+        });
+
+        let mut client_config = TransportConfig::default();
+        client_config.tls.allow_insecure = true;
+        let client = TransportLayer::new(client_config).unwrap();
+
+        client.connect(&addr.to_string()).await.unwrap();
+        let first_local_addr = client.client_endpoint().await.unwrap().local_addr().unwrap();
+
+        // Force a second real connect instead of serving it from the pool.
+        client.connection_pool.remove(&addr.to_string());
+        client.connect(&addr.to_string()).await.unwrap();
+        let second_local_addr = client.client_endpoint().await.unwrap().local_addr().unwrap();
+
+        assert_eq!(first_local_addr, second_local_addr);
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), server_task)
+            .await
+            .expect("server never accepted both connections")
+            .unwrap();
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_send_datagram_delivers_to_peer() {
+        let dir = std::env::temp_dir().join(format!("aimesh-datagram-test-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+
+        let mut server_config = TransportConfig::default();
+        server_config.bind_addr = "127.0.0.1:0".into();
+        server_config.tls.cert_path = Some(cert_path);
+        server_config.tls.key_path = Some(key_path);
+        server_config.tls.allow_insecure = true;
+
+        let mut server = TransportLayer::new(server_config).unwrap();
+        server.listen().await.unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let server_task = tokio::spawn(async move {
+            let conn = server.accept().await.unwrap();
+            server.read_datagram(&conn).await.unwrap()
+        });
+
+        let mut client_config = TransportConfig::default();
+        client_config.tls.allow_insecure = true;
+        let client = TransportLayer::new(client_config).unwrap();
+
+        client.send_datagram(&addr.to_string(), b"telemetry".to_vec()).await.unwrap();
+
+        let received = tokio::time::timeout(std::time::Duration::from_secs(5), server_task)
+            .await
+            .expect("server never received the datagram")
+            .unwrap();
+        assert_eq!(received, b"telemetry".to_vec());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_send_datagram_rejects_oversized_payload() {
+        let dir = std::env::temp_dir().join(format!("aimesh-datagram-oversize-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+
+        let mut server_config = TransportConfig::default();
+        server_config.bind_addr = "127.0.0.1:0".into();
+        server_config.tls.cert_path = Some(cert_path);
+        server_config.tls.key_path = Some(key_path);
+        server_config.tls.allow_insecure = true;
+
+        let mut server = TransportLayer::new(server_config).unwrap();
+        server.listen().await.unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let server_task = tokio::spawn(async move {
+            server.accept().await.unwrap();
+        });
+
+        let mut client_config = TransportConfig::default();
+        client_config.tls.allow_insecure = true;
+        let client = TransportLayer::new(client_config).unwrap();
+
+        let oversized = vec![0u8; 64 * 1024];
+        let result = client.send_datagram(&addr.to_string(), oversized).await;
+        assert!(matches!(result, Err(TransportError::PayloadTooLarge { .. })));
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), server_task).await.ok();
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_scaled_concurrent_streams_interpolates_between_min_and_max() {
+        let mut config = TransportConfig::default();
+        config.min_concurrent_streams = 10;
+        config.max_concurrent_streams = 1000;
+        let transport = TransportLayer::new(config).unwrap();
+
+        assert_eq!(transport.scaled_concurrent_streams(1.0), 1000);
+        assert_eq!(transport.scaled_concurrent_streams(0.0), 10);
+        assert_eq!(transport.scaled_concurrent_streams(0.5), 505);
+        // Out-of-range ratios are clamped rather than over/under-shooting.
+        assert_eq!(transport.scaled_concurrent_streams(2.0), 1000);
+        assert_eq!(transport.scaled_concurrent_streams(-1.0), 10);
+    }
+
+    #[tokio::test]
+    async fn test_accept_for_agent_throttles_connection_from_router_budget() {
+        use crate::routing::{CostAwareRouter, RouterConfig};
+
+        let dir = std::env::temp_dir().join(format!("aimesh-flow-control-test-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+
+        let mut server_config = TransportConfig::default();
+        server_config.bind_addr = "127.0.0.1:0".into();
+        server_config.tls.cert_path = Some(cert_path);
+        server_config.tls.key_path = Some(key_path);
+        server_config.tls.allow_insecure = true;
+        server_config.min_concurrent_streams = 10;
+        server_config.max_concurrent_streams = 1000;
+
+        let mut server = TransportLayer::new(server_config).unwrap();
+        server.listen().await.unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let router = CostAwareRouter::new(RouterConfig::default());
+        router.set_budget("noisy-agent", 100.0, i64::MAX);
+        router.consume_budget("noisy-agent", 90.0).unwrap();
+
+        let server_task = tokio::spawn(async move {
+            let conn = server.accept_for_agent("noisy-agent", &router).await.unwrap();
+            // Just confirm the connection is still usable after narrowing
+            // its stream cap dynamically.
+            conn.close_reason().is_none()
+        });
+
+        let mut client_config = TransportConfig::default();
+        client_config.tls.allow_insecure = true;
+        let client = TransportLayer::new(client_config).unwrap();
+        client.connect(&addr.to_string()).await.unwrap();
+
+        let still_open = tokio::time::timeout(std::time::Duration::from_secs(5), server_task)
+            .await
+            .expect("server never accepted for the throttled agent")
+            .unwrap();
+        assert!(still_open);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_read_message_rejects_frame_with_wrong_magic_byte() {
+        let dir = std::env::temp_dir().join(format!("aimesh-framing-test-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+
+        let mut server_config = TransportConfig::default();
+        server_config.bind_addr = "127.0.0.1:0".into();
+        server_config.tls.cert_path = Some(cert_path);
+        server_config.tls.key_path = Some(key_path);
+        server_config.tls.allow_insecure = true;
+
+        let mut server = TransportLayer::new(server_config).unwrap();
+        server.listen().await.unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let server_task = tokio::spawn(async move {
+            let conn = server.accept().await.unwrap();
+            let (_send, mut recv) = conn.accept_bi().await.unwrap();
+            server.read_message(&mut recv).await
+        });
+
+        let mut client_config = TransportConfig::default();
+        client_config.tls.allow_insecure = true;
+        let client = TransportLayer::new(client_config).unwrap();
+        let connection = client.connect(&addr.to_string()).await.unwrap();
+
+        let (mut send, _recv) = connection.open_bi().await.unwrap();
+        // A well-formed length prefix, but the wrong magic byte.
+        let mut bad_frame = vec![0xFFu8, PROTOCOL_VERSION];
+        bad_frame.extend_from_slice(&0u32.to_be_bytes());
+        send.write_all(&bad_frame).await.unwrap();
+        send.finish().unwrap();
+
+        let result = tokio::time::timeout(std::time::Duration::from_secs(5), server_task)
+            .await
+            .expect("server never read the frame")
+            .unwrap();
+        assert!(matches!(result, Err(TransportError::ProtocolMismatch { .. })));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_read_message_rejects_oversized_length_prefix_before_allocating() {
+        let dir = std::env::temp_dir().join(format!("aimesh-framing-oversize-{}", uuid::Uuid::now_v7()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+
+        let mut server_config = TransportConfig::default();
+        server_config.bind_addr = "127.0.0.1:0".into();
+        server_config.tls.cert_path = Some(cert_path);
+        server_config.tls.key_path = Some(key_path);
+        server_config.tls.allow_insecure = true;
+
+        let mut server = TransportLayer::new(server_config).unwrap();
+        server.listen().await.unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let server_task = tokio::spawn(async move {
+            let conn = server.accept().await.unwrap();
+            let (_send, mut recv) = conn.accept_bi().await.unwrap();
+            server.read_message(&mut recv).await
+        });
+
+        let mut client_config = TransportConfig::default();
+        client_config.tls.allow_insecure = true;
+        let client = TransportLayer::new(client_config).unwrap();
+        let connection = client.connect(&addr.to_string()).await.unwrap();
+
+        let (mut send, _recv) = connection.open_bi().await.unwrap();
+        // A well-formed header claiming a length near u32::MAX, with no
+        // payload bytes ever sent. A pre-allocation check must reject this
+        // from the header alone instead of blocking on a `read_exact` for
+        // data that will never arrive.
+        let mut huge_frame = vec![FRAME_MAGIC, PROTOCOL_VERSION];
+        huge_frame.extend_from_slice(&u32::MAX.to_be_bytes());
+        send.write_all(&huge_frame).await.unwrap();
+        send.finish().unwrap();
+
+        let result = tokio::time::timeout(std::time::Duration::from_secs(5), server_task)
+            .await
+            .expect("server never rejected the oversized frame")
+            .unwrap();
+        assert!(matches!(
+            result,
+            Err(TransportError::PayloadTooLarge { size, max })
+                if size == u32::MAX as usize && max == MAX_FRAME_PAYLOAD_SIZE
+        ));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
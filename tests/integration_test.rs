@@ -1,10 +1,14 @@
 //! Integration tests for AiMesh
 
+use std::sync::Arc;
+
 use aimesh::{
-    AiMesh, AiMeshConfig, AiMessage, 
+    AiMesh, AiMeshConfig, AiMessage,
     EndpointMetrics, HealthStatus,
-    StorageConfig,
+    StorageConfig, TransportConfig, TransportLayer,
+    FilterError, FilterOutcome, MessageFilter,
 };
+use async_trait::async_trait;
 
 /// Test that AiMesh can be created with default config
 #[tokio::test]
@@ -158,14 +162,35 @@ async fn test_message_validation() {
     assert!(invalid.validate().is_err());
 }
 
-/// Test full message processing flow
+/// Test full message processing flow, including real delivery over a QUIC
+/// connection to the routed endpoint
 #[tokio::test]
 async fn test_full_message_flow() {
-    let mesh = AiMesh::new(AiMeshConfig::default()).unwrap();
-    
-    // Register endpoint
+    // Stand in for the downstream endpoint: a real QUIC listener that reads
+    // one message and echoes back a fixed acknowledgment payload.
+    let mut backend_config = TransportConfig::default();
+    backend_config.bind_addr = "127.0.0.1:0".into();
+    backend_config.tls.allow_insecure = true;
+    let mut backend = TransportLayer::new(backend_config).unwrap();
+    backend.listen().await.unwrap();
+    let backend_addr = backend.local_addr().unwrap();
+
+    let backend_task = tokio::spawn(async move {
+        let conn = backend.accept().await.unwrap();
+        let (mut send, mut recv) = conn.accept_bi().await.unwrap();
+        let received = backend.read_message(&mut recv).await.unwrap();
+        backend.write_message(&mut send, b"ack-from-backend").await.unwrap();
+        send.finish().unwrap();
+        received
+    });
+
+    let mut mesh_config = AiMeshConfig::default();
+    mesh_config.transport.tls.allow_insecure = true;
+    let mesh = AiMesh::new(mesh_config).unwrap();
+
+    // Register endpoint using its real dialable address
     mesh.router.register_endpoint(EndpointMetrics {
-        endpoint_id: "backend".into(),
+        endpoint_id: backend_addr.to_string(),
         capacity: 100,
         current_load: 0,
         cost_per_1k_tokens: 10.0,
@@ -174,10 +199,10 @@ async fn test_full_message_flow() {
         last_health_check: 0,
         health_status: HealthStatus::Healthy as i32,
     });
-    
+
     // Set budget
     mesh.router.set_budget("flow-agent", 1000.0, i64::MAX);
-    
+
     // Process message
     let msg = AiMessage::new(
         "flow-agent".into(),
@@ -185,14 +210,205 @@ async fn test_full_message_flow() {
         100.0,
         i64::MAX,
     );
-    
-    let result = mesh.process_message(msg).await;
+
+    let result = mesh.process_message(msg, None).await;
     assert!(result.is_ok());
-    
+
     let ack = result.unwrap();
     assert!(ack.is_success());
-    
+    assert_eq!(ack.result, b"ack-from-backend".to_vec());
+
+    let delivered = tokio::time::timeout(std::time::Duration::from_secs(5), backend_task)
+        .await
+        .expect("backend never received the message")
+        .unwrap();
+    let decoded = AiMessage::deserialize(&delivered).unwrap();
+    assert_eq!(decoded.agent_id, "flow-agent");
+
     // Check stats
     let stats = mesh.get_stats();
     assert_eq!(stats.observability.messages_total, 1);
 }
+
+/// Test that a logical `endpoint_id` distinct from its dialable address is
+/// resolved through `register_endpoint_address` rather than relying on
+/// `resolve_endpoint`'s literal-address fallback
+#[tokio::test]
+async fn test_message_flow_resolves_logical_endpoint_id_to_registered_address() {
+    let mut backend_config = TransportConfig::default();
+    backend_config.bind_addr = "127.0.0.1:0".into();
+    backend_config.tls.allow_insecure = true;
+    let mut backend = TransportLayer::new(backend_config).unwrap();
+    backend.listen().await.unwrap();
+    let backend_addr = backend.local_addr().unwrap();
+
+    let backend_task = tokio::spawn(async move {
+        let conn = backend.accept().await.unwrap();
+        let (mut send, mut recv) = conn.accept_bi().await.unwrap();
+        let received = backend.read_message(&mut recv).await.unwrap();
+        backend.write_message(&mut send, b"ack-from-backend").await.unwrap();
+        send.finish().unwrap();
+        received
+    });
+
+    let mut mesh_config = AiMeshConfig::default();
+    mesh_config.transport.tls.allow_insecure = true;
+    let mesh = AiMesh::new(mesh_config).unwrap();
+
+    // Route by a logical endpoint id, not the literal dialable address, and
+    // map the two via `register_endpoint_address` the way `main.rs` does.
+    mesh.router.register_endpoint(EndpointMetrics {
+        endpoint_id: "logical-backend".into(),
+        capacity: 100,
+        current_load: 0,
+        cost_per_1k_tokens: 10.0,
+        latency_p99_ms: 100.0,
+        error_rate: 0.0,
+        last_health_check: 0,
+        health_status: HealthStatus::Healthy as i32,
+    });
+    mesh.transport.register_endpoint_address("logical-backend", &backend_addr.to_string());
+
+    mesh.router.set_budget("flow-agent", 1000.0, i64::MAX);
+
+    let msg = AiMessage::new(
+        "flow-agent".into(),
+        b"Test message".to_vec(),
+        100.0,
+        i64::MAX,
+    );
+
+    let result = mesh.process_message(msg, None).await;
+    assert!(result.is_ok());
+
+    let ack = result.unwrap();
+    assert!(ack.is_success());
+    assert_eq!(ack.result, b"ack-from-backend".to_vec());
+
+    let delivered = tokio::time::timeout(std::time::Duration::from_secs(5), backend_task)
+        .await
+        .expect("backend never received the message")
+        .unwrap();
+    let decoded = AiMessage::deserialize(&delivered).unwrap();
+    assert_eq!(decoded.agent_id, "flow-agent");
+}
+
+/// Test that low-priority messages deliver as unreliable datagrams instead
+/// of a reliable stream, and still succeed without a downstream response
+#[tokio::test]
+async fn test_low_priority_message_delivers_as_datagram() {
+    let mut backend_config = TransportConfig::default();
+    backend_config.bind_addr = "127.0.0.1:0".into();
+    backend_config.tls.allow_insecure = true;
+    let mut backend = TransportLayer::new(backend_config).unwrap();
+    backend.listen().await.unwrap();
+    let backend_addr = backend.local_addr().unwrap();
+
+    let backend_task = tokio::spawn(async move {
+        let conn = backend.accept().await.unwrap();
+        backend.read_datagram(&conn).await.unwrap()
+    });
+
+    let mut mesh_config = AiMeshConfig::default();
+    mesh_config.transport.tls.allow_insecure = true;
+    let mesh = AiMesh::new(mesh_config).unwrap();
+
+    mesh.router.register_endpoint(EndpointMetrics {
+        endpoint_id: backend_addr.to_string(),
+        capacity: 100,
+        current_load: 0,
+        cost_per_1k_tokens: 10.0,
+        latency_p99_ms: 100.0,
+        error_rate: 0.0,
+        last_health_check: 0,
+        health_status: HealthStatus::Healthy as i32,
+    });
+    mesh.router.set_budget("telemetry-agent", 1000.0, i64::MAX);
+
+    let mut msg = AiMessage::new(
+        "telemetry-agent".into(),
+        b"low priority telemetry".to_vec(),
+        10.0,
+        i64::MAX,
+    );
+    msg.priority = 10; // PriorityLevel::Low
+
+    let result = mesh.process_message(msg, None).await.unwrap();
+    assert!(result.is_success());
+    assert!(result.result.is_empty());
+
+    let delivered = tokio::time::timeout(std::time::Duration::from_secs(5), backend_task)
+        .await
+        .expect("backend never received the datagram")
+        .unwrap();
+    let decoded = AiMessage::deserialize(&delivered).unwrap();
+    assert_eq!(decoded.agent_id, "telemetry-agent");
+}
+
+/// Test that a mismatched peer identity is rejected before routing
+#[tokio::test]
+async fn test_process_message_rejects_mismatched_peer_identity() {
+    let mesh = AiMesh::new(AiMeshConfig::default()).unwrap();
+    mesh.router.set_budget("flow-agent", 1000.0, i64::MAX);
+    mesh.tenants.bind_identity("flow-agent", "flow-agent.aimesh");
+
+    let msg = AiMessage::new(
+        "flow-agent".into(),
+        b"Test message".to_vec(),
+        100.0,
+        i64::MAX,
+    );
+
+    let result = mesh.process_message(msg, Some("someone-else.aimesh")).await;
+    assert!(matches!(result, Err(aimesh::AiMeshError::Unauthorized(_))));
+}
+
+/// A filter that rejects a specific agent before routing, standing in for
+/// a third-party policy check.
+struct DenylistFilter {
+    denied_agent: String,
+}
+
+#[async_trait]
+impl MessageFilter for DenylistFilter {
+    fn name(&self) -> &str {
+        "denylist-filter"
+    }
+
+    async fn on_ingress(&self, message: &mut AiMessage) -> Result<FilterOutcome, FilterError> {
+        if message.agent_id == self.denied_agent {
+            return Ok(FilterOutcome::ShortCircuit(
+                aimesh::AcknowledgmentMessage::failure(
+                    message.message_id.clone(),
+                    "agent denied by policy".into(),
+                ),
+            ));
+        }
+        Ok(FilterOutcome::Continue)
+    }
+}
+
+/// Test that a registered filter can short-circuit process_message before
+/// the message is ever routed or delivered.
+#[tokio::test]
+async fn test_registered_filter_short_circuits_process_message() {
+    let mesh = AiMesh::new(AiMeshConfig::default()).unwrap();
+    mesh.register_filter(Arc::new(DenylistFilter {
+        denied_agent: "blocked-agent".into(),
+    }));
+    mesh.router.set_budget("blocked-agent", 1000.0, i64::MAX);
+
+    let msg = AiMessage::new(
+        "blocked-agent".into(),
+        b"Test message".to_vec(),
+        100.0,
+        i64::MAX,
+    );
+
+    let ack = mesh.process_message(msg, None).await.unwrap();
+    assert!(!ack.is_success());
+    assert_eq!(ack.error, "agent denied by policy");
+
+    // Rejected before routing ever ran, so no budget was consumed.
+    assert_eq!(mesh.router.get_remaining_budget("blocked-agent"), 1000.0);
+}